@@ -0,0 +1,274 @@
+#![allow(dead_code)]
+/*
+    Text statistics used to break classical ciphers.
+
+    Frequency tables
+    How many times each symbol (monographic), pair of symbols (bigram) or triple of symbols (trigram)
+    appears in the text, substitution ciphers move the frequencies around but don't change their shape.
+
+    Index of coincidence
+    The probability of picking two equal symbols from the text, sum(n * (n - 1)) / (N * (N - 1)).
+    Random text over 27 symbols has 1/27 ≈ 0.037, english (spaces included) is close to 0.077,
+    any substitution keeps the index, any polyalphabetic cipher pushes it towards random.
+
+    Periodic index of coincidence
+    The average index of the columns we get by splitting the text every `period` symbols,
+    when the period matches the length of a vigenere secret every column looks like the language again.
+
+    Chi-squared
+    How far the observed frequencies are from the ones expected for a language, sum((O - E)^2 / E),
+    the lower the value, the closer the text is to that language.
+
+    Entropy
+    Average amount of information per symbol in bits, -sum(p * log2(p)).
+    Random text over 27 symbols has log2(27) ≈ 4.75 bits, natural languages have much less.
+
+    Every function works over an alphabet and ignores symbols outside of it,
+    the reference languages use the uppercase alphabet (space plus A to Z).
+*/
+
+use crate::symmetric::alphabet::Alphabet;
+use std::collections::HashMap;
+
+pub mod fitness;
+pub mod identify;
+pub mod meet_in_the_middle;
+
+// Relative frequency of each symbol of the uppercase alphabet, spaces included.
+const ENGLISH: [f64; 27] = [
+    0.1828, 0.0667, 0.0122, 0.0227, 0.0348, 0.1038, 0.0182, 0.0165, 0.0498, 0.0569, 0.0013, 0.0063,
+    0.0329, 0.0197, 0.0552, 0.0613, 0.0158, 0.0008, 0.0489, 0.0517, 0.0740, 0.0225, 0.0080, 0.0193,
+    0.0012, 0.0161, 0.0006,
+];
+
+const PORTUGUESE: [f64; 27] = [
+    0.1700, 0.1214, 0.0086, 0.0322, 0.0414, 0.1043, 0.0085, 0.0108, 0.0106, 0.0513, 0.0033, 0.0002,
+    0.0231, 0.0393, 0.0419, 0.0891, 0.0209, 0.0100, 0.0542, 0.0648, 0.0360, 0.0384, 0.0139, 0.0001,
+    0.0017, 0.0001, 0.0039,
+];
+
+const SPANISH: [f64; 27] = [
+    0.1700, 0.0986, 0.0189, 0.0344, 0.0429, 0.1042, 0.0059, 0.0151, 0.0060, 0.0534, 0.0042, 0.0001,
+    0.0425, 0.0270, 0.0574, 0.0743, 0.0215, 0.0075, 0.0588, 0.0682, 0.0396, 0.0250, 0.0097, 0.0001,
+    0.0018, 0.0086, 0.0040,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Portuguese,
+    Spanish,
+}
+
+impl Language {
+    pub fn frequencies(&self) -> &'static [f64; 27] {
+        match self {
+            Self::English => &ENGLISH,
+            Self::Portuguese => &PORTUGUESE,
+            Self::Spanish => &SPANISH,
+        }
+    }
+
+    // Index of coincidence expected for a long text written in the language.
+    pub fn index_of_coincidence(&self) -> f64 {
+        self.frequencies().iter().map(|f| f * f).sum()
+    }
+}
+
+// Positions of the symbols of the text that belong to the alphabet.
+pub fn symbols(text: &str, alphabet: &Alphabet) -> Vec<usize> {
+    text.chars().filter_map(|c| alphabet.position(c)).collect()
+}
+
+// Number of times each symbol of the alphabet appears, indexed by the symbol position.
+pub fn frequencies(text: &str, alphabet: &Alphabet) -> Vec<usize> {
+    let mut counts = vec![0; alphabet.len()];
+    for s in symbols(text, alphabet) {
+        counts[s] += 1;
+    }
+    counts
+}
+
+pub fn relative_frequencies(text: &str, alphabet: &Alphabet) -> Vec<f64> {
+    let counts = frequencies(text, alphabet);
+    let total = counts.iter().sum::<usize>().max(1) as f64;
+    counts.into_iter().map(|c| c as f64 / total).collect()
+}
+
+// Number of times each sequence of `n` consecutive symbols appears.
+pub fn ngrams(text: &str, alphabet: &Alphabet, n: usize) -> HashMap<String, usize> {
+    let mut result = HashMap::new();
+    if n == 0 {
+        return result;
+    }
+
+    for window in symbols(text, alphabet).windows(n) {
+        let ngram = window
+            .iter()
+            .map(|s| alphabet.symbol(*s))
+            .collect::<String>();
+        *result.entry(ngram).or_insert(0) += 1;
+    }
+    result
+}
+
+pub fn bigrams(text: &str, alphabet: &Alphabet) -> HashMap<String, usize> {
+    ngrams(text, alphabet, 2)
+}
+
+pub fn trigrams(text: &str, alphabet: &Alphabet) -> HashMap<String, usize> {
+    ngrams(text, alphabet, 3)
+}
+
+// Index of coincidence of symbols already mapped to positions of an alphabet with `alphabet_length` symbols.
+pub fn coincidence(symbols: &[usize], alphabet_length: usize) -> f64 {
+    if symbols.len() < 2 {
+        return 0.0;
+    }
+
+    let mut counts = vec![0usize; alphabet_length];
+    for s in symbols {
+        counts[*s] += 1;
+    }
+
+    let total = symbols.len() as f64;
+    counts
+        .iter()
+        .map(|&c| (c * c.saturating_sub(1)) as f64)
+        .sum::<f64>()
+        / (total * (total - 1.0))
+}
+
+pub fn index_of_coincidence(text: &str, alphabet: &Alphabet) -> f64 {
+    coincidence(&symbols(text, alphabet), alphabet.len())
+}
+
+pub fn periodic_index_of_coincidence(text: &str, alphabet: &Alphabet, period: usize) -> f64 {
+    periodic_coincidence(&symbols(text, alphabet), alphabet.len(), period)
+}
+
+pub fn periodic_coincidence(symbols: &[usize], alphabet_length: usize, period: usize) -> f64 {
+    if period == 0 {
+        return 0.0;
+    }
+
+    (0..period)
+        .map(|offset| {
+            let column = symbols
+                .iter()
+                .skip(offset)
+                .step_by(period)
+                .copied()
+                .collect::<Vec<usize>>();
+            coincidence(&column, alphabet_length)
+        })
+        .sum::<f64>()
+        / period as f64
+}
+
+// A text without any symbol of the alphabet can't look like the language, so it gets the worst score.
+pub fn chi_squared(text: &str, language: Language) -> f64 {
+    let counts = frequencies(text, &Alphabet::uppercase());
+    let total = counts.iter().sum::<usize>() as f64;
+    if total == 0.0 {
+        return f64::INFINITY;
+    }
+    counts
+        .iter()
+        .zip(language.frequencies().iter())
+        .map(|(&observed, &frequency)| {
+            let expected = total * frequency;
+            (observed as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+// Chi-squared per symbol against english, english text (or a transposition of it) stays below it.
+pub const ENGLISH_CHI_SQUARED_PER_SYMBOL: f64 = 0.3;
+
+// Chi-squared divided by the number of symbols, so texts of any length can be compared with the same threshold.
+pub fn chi_squared_per_symbol(text: &str, language: Language) -> f64 {
+    let length = symbols(text, &Alphabet::uppercase()).len().max(1);
+    chi_squared(text, language) / length as f64
+}
+
+// Sum of the log probabilities of each symbol in the language, the higher the more likely.
+pub fn log_likelihood(text: &str, language: Language) -> f64 {
+    symbols(text, &Alphabet::uppercase())
+        .into_iter()
+        .map(|s| language.frequencies()[s].ln())
+        .sum()
+}
+
+pub fn entropy(text: &str, alphabet: &Alphabet) -> f64 {
+    relative_frequencies(text, alphabet)
+        .into_iter()
+        .filter(|p| *p > 0.0)
+        .map(|p| -p * p.log2())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str =
+        "IT WAS THE BEST OF TIMES IT WAS THE WORST OF TIMES IT WAS THE AGE OF WISDOM \
+        IT WAS THE AGE OF FOOLISHNESS IT WAS THE EPOCH OF BELIEF IT WAS THE EPOCH OF INCREDULITY";
+
+    #[test]
+    fn frequency_tables() {
+        let alphabet = Alphabet::uppercase();
+        let counts = frequencies("Hello, World!", &alphabet);
+        assert_eq!(counts.len(), 27);
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts[alphabet.position('L').unwrap()], 3);
+        assert_eq!(counts.iter().sum::<usize>(), 11);
+
+        let relative = relative_frequencies("AAB", &alphabet);
+        assert!((relative[1] - 2.0 / 3.0).abs() < 1e-9);
+
+        let bigrams = bigrams("Hello, Hello", &alphabet);
+        assert_eq!(bigrams.get("LL"), Some(&2));
+        assert_eq!(bigrams.get("O "), Some(&1));
+
+        let trigrams = trigrams(TEXT, &alphabet);
+        assert_eq!(trigrams.get("THE"), Some(&6));
+        assert!(ngrams(TEXT, &alphabet, 0).is_empty());
+    }
+
+    #[test]
+    fn index_of_coincidence_of_text() {
+        let alphabet = Alphabet::uppercase();
+        assert_eq!(index_of_coincidence("AAAA", &alphabet), 1.0);
+        assert_eq!(index_of_coincidence("ABCD", &alphabet), 0.0);
+        assert_eq!(index_of_coincidence("A", &alphabet), 0.0);
+
+        let english = index_of_coincidence(TEXT, &alphabet);
+        assert!(english > 0.06);
+        assert!((Language::English.index_of_coincidence() - 0.077).abs() < 0.001);
+
+        // every column of a period 2 text is a single repeated letter
+        assert_eq!(periodic_index_of_coincidence("ABABABAB", &alphabet, 2), 1.0);
+        assert_eq!(periodic_index_of_coincidence("ABABABAB", &alphabet, 0), 0.0);
+    }
+
+    #[test]
+    fn chi_squared_against_languages() {
+        let english = chi_squared(TEXT, Language::English);
+        assert!(english < chi_squared(TEXT, Language::Portuguese));
+        assert!(english < chi_squared("QXZJ QXZJ KKVW", Language::English));
+        assert_eq!(chi_squared("", Language::English), f64::INFINITY);
+        assert_eq!(chi_squared("123!?", Language::English), f64::INFINITY);
+
+        assert!(log_likelihood(TEXT, Language::English) > log_likelihood(TEXT, Language::Spanish));
+    }
+
+    #[test]
+    fn entropy_of_text() {
+        let alphabet = Alphabet::uppercase();
+        assert_eq!(entropy("AAAA", &alphabet), 0.0);
+        assert_eq!(entropy("ABAB", &alphabet), 1.0);
+        assert!(entropy(TEXT, &alphabet) < (27f64).log2());
+    }
+}
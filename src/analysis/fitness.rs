@@ -0,0 +1,216 @@
+/*
+    Fitness: how much a text looks like english, used by the crackers to rank candidates automatically.
+
+    Quadgrams
+    Every sequence of 4 symbols of the text is looked up in a table with the log probability of the
+    sequence in english, the sum of those values is the score of the text, the higher the better.
+    Unlike chi-squared, quadgrams know about the order of the letters, "THE " is very likely
+    while "EHT " is not, even though both have the same letters.
+
+    The table was built from around one million symbols of english prose (free software licenses and
+    python documentation), every run of symbols outside of A to Z is a single space, spaces are written
+    as `_` in the file and sequences seen only once are left out.
+    Sequences missing from the table get a floor probability instead of zero, so one rare word
+    doesn't make the whole text impossible.
+
+    Bigrams
+    The same idea with pairs of symbols, counted from the first two symbols of each quadgram of the table.
+
+    Chi-squared
+    Also available as a fitness, negated so that every fitness agrees that higher is better.
+    It only looks at symbol frequencies, so it is the right choice when the symbols aren't contiguous text,
+    like the columns of a vigenere cipher.
+*/
+
+use crate::analysis::{self, Language};
+use crate::symmetric::alphabet::Alphabet;
+use std::sync::OnceLock;
+
+const ENGLISH_QUADGRAMS: &str = include_str!("english_quadgrams.txt");
+
+// Scores are computed over the uppercase alphabet, space plus A to Z.
+const SYMBOLS: usize = 27;
+const QUADGRAMS: usize = SYMBOLS * SYMBOLS * SYMBOLS * SYMBOLS;
+
+pub trait Fitness {
+    // The higher the score, the more the text looks like the language.
+    fn score(&self, text: &str) -> f64;
+}
+
+// Parses lines like `TION 4859`, where `_` stands for a space.
+fn quadgram_counts(table: &str) -> Vec<u64> {
+    let mut counts = vec![0u64; QUADGRAMS];
+    let alphabet = Alphabet::uppercase();
+
+    for line in table.lines() {
+        let Some((quadgram, count)) = line.split_once(' ') else {
+            continue;
+        };
+        let symbols = quadgram
+            .chars()
+            .filter_map(|c| alphabet.position(if c == '_' { ' ' } else { c }))
+            .collect::<Vec<usize>>();
+        if let (4, Ok(count)) = (symbols.len(), count.parse::<u64>()) {
+            counts[index(&symbols)] += count;
+        }
+    }
+
+    counts
+}
+
+// log10 probability of each count, the ones never seen get a floor probability.
+fn log_probabilities(counts: Vec<u64>) -> Vec<f64> {
+    let total = counts.iter().sum::<u64>().max(1) as f64;
+    let floor = (0.01 / total).log10();
+    counts
+        .into_iter()
+        .map(|c| {
+            if c == 0 {
+                floor
+            } else {
+                (c as f64 / total).log10()
+            }
+        })
+        .collect()
+}
+
+pub struct Quadgrams {
+    // log10 probability of each quadgram, indexed by its symbols in base 27
+    scores: Vec<f64>,
+}
+
+impl Quadgrams {
+    // The table is parsed the first time it's needed and shared by every cracker afterwards.
+    pub fn english() -> &'static Self {
+        static ENGLISH: OnceLock<Quadgrams> = OnceLock::new();
+        ENGLISH.get_or_init(|| Self {
+            scores: log_probabilities(quadgram_counts(ENGLISH_QUADGRAMS)),
+        })
+    }
+
+    // Score of symbols already mapped to positions of the uppercase alphabet.
+    pub fn score_symbols(&self, symbols: &[usize]) -> f64 {
+        symbols.windows(4).map(|w| self.scores[index(w)]).sum()
+    }
+}
+
+impl Fitness for Quadgrams {
+    fn score(&self, text: &str) -> f64 {
+        self.score_symbols(&normalize(text))
+    }
+}
+
+// Pairs of symbols, their counts come from the first two symbols of each quadgram of the same table.
+// Weaker than quadgrams, but they still work when the text is only partially in the right order.
+pub struct Bigrams {
+    scores: Vec<f64>,
+}
+
+impl Bigrams {
+    pub fn english() -> &'static Self {
+        static ENGLISH: OnceLock<Bigrams> = OnceLock::new();
+        ENGLISH.get_or_init(|| {
+            let mut counts = vec![0u64; SYMBOLS * SYMBOLS];
+            for (quadgram, count) in quadgram_counts(ENGLISH_QUADGRAMS).into_iter().enumerate() {
+                counts[quadgram / (SYMBOLS * SYMBOLS)] += count;
+            }
+            Self {
+                scores: log_probabilities(counts),
+            }
+        })
+    }
+
+    pub fn score_symbols(&self, symbols: &[usize]) -> f64 {
+        symbols.windows(2).map(|w| self.scores[index(w)]).sum()
+    }
+}
+
+impl Fitness for Bigrams {
+    fn score(&self, text: &str) -> f64 {
+        self.score_symbols(&normalize(text))
+    }
+}
+
+pub struct ChiSquared(pub Language);
+
+impl Fitness for ChiSquared {
+    fn score(&self, text: &str) -> f64 {
+        -analysis::chi_squared(text, self.0)
+    }
+}
+
+fn index(quadgram: &[usize]) -> usize {
+    quadgram.iter().fold(0, |index, s| index * SYMBOLS + s)
+}
+
+// Uppercase symbols of the text, anything outside of the alphabet counts as a space like in the table.
+fn normalize(text: &str) -> Vec<usize> {
+    let alphabet = Alphabet::uppercase();
+    let mut symbols: Vec<usize> = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        let symbol = alphabet.position(c).unwrap_or(0);
+        if symbol != 0 || symbols.last() != Some(&0) {
+            symbols.push(symbol);
+        }
+    }
+    symbols
+}
+
+// Quadgram score of the text in english.
+pub fn score(text: &str) -> f64 {
+    Quadgrams::english().score(text)
+}
+
+// Scores every (key, candidate) pair and sorts them from the most to the least likely.
+pub fn rank<K>(
+    candidates: impl IntoIterator<Item = (K, String)>,
+    fitness: &dyn Fitness,
+) -> Vec<(K, f64, String)> {
+    let mut result = candidates
+        .into_iter()
+        .map(|(key, text)| {
+            let score = fitness.score(&text);
+            (key, score, text)
+        })
+        .collect::<Vec<(K, f64, String)>>();
+
+    result.sort_by(|a, b| b.1.total_cmp(&a.1));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_scores_higher_than_garbage() {
+        let english = score("ATTACK THE EAST WALL OF THE CASTLE AT DAWN");
+        let shuffled = score("TACKAT EHT SETA LWLA FO HET TLECAS AT WAND");
+        let random = score("QXZJV KWPQ ZJXV QKXZ JWVQ PZKX JVQW XZKP QVJ");
+        assert!(english > shuffled);
+        assert!(shuffled > random);
+
+        // case and punctuation don't change the score
+        assert_eq!(score("Hello, World!"), score("HELLO WORLD "));
+        assert_eq!(score("ABC"), 0.0);
+
+        let bigrams = Bigrams::english();
+        assert!(bigrams.score("THE EAST WALL") > bigrams.score("HTE AETS AWLL"));
+    }
+
+    #[test]
+    fn rank_candidates() {
+        let candidates = vec![
+            (1, "WKLV LV D WHVW".to_string()),
+            (0, "THIS IS A TEST".to_string()),
+            (2, "XLMW MW E XIWX".to_string()),
+        ];
+
+        let ranked = rank(candidates.clone(), Quadgrams::english());
+        assert_eq!(ranked[0].0, 0);
+        assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        let ranked = rank(candidates, &ChiSquared(Language::English));
+        assert_eq!(ranked[0].2, "THIS IS A TEST");
+    }
+}
@@ -0,0 +1,278 @@
+/*
+    Identifying the cipher of an unknown cipher text.
+
+    Each family of classical ciphers leaves its own marks on the statistics of the text:
+
+    -> Transposition: the symbols are only moved, so the index of coincidence and the symbol
+       frequencies are still the english ones.
+    -> Caesar / affine (monoalphabetic substitution): the index of coincidence is still the english one,
+       but the frequencies belong to other symbols, caesar only shifts them, so one of the shifts
+       suggested by the most frequent letter brings the english frequencies back.
+    -> Vigenere family: the index is pushed towards random, but splitting the text in columns
+       with the right period gives the english index again.
+    -> Playfair style (digraphic): only letters, an even number of them, no J and never the same letter twice
+       in a pair, random text of the same length would have a few of those pairs.
+    -> One time pad / random: the index is close to random and no period brings it back.
+
+    Every test gives a confidence between 0 and 1 to a family, they are not probabilities,
+    short texts are hard to identify so every confidence should be taken with a grain of salt.
+*/
+
+use crate::analysis::{self, Language};
+use crate::symmetric::alphabet::Alphabet;
+use crate::symmetric::transposition_cipher::{self, Family};
+use crate::symmetric::{caesar_cipher, vigenere_cipher};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherFamily {
+    Transposition,
+    Monoalphabetic,
+    Polyalphabetic { period: usize },
+    Digraphic,
+    Random,
+}
+
+impl fmt::Display for CipherFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transposition => write!(f, "transposition (rail fence, columnar)"),
+            Self::Monoalphabetic => write!(f, "monoalphabetic substitution (caesar, affine)"),
+            Self::Polyalphabetic { period } => {
+                write!(f, "vigenere family, period {}", period)
+            }
+            Self::Digraphic => write!(f, "digraphic (playfair)"),
+            Self::Random => write!(f, "one time pad or random"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hypothesis {
+    pub family: CipherFamily,
+    pub confidence: f64,
+    // The statistics that support the hypothesis.
+    pub evidence: String,
+}
+
+// Chi-squared per symbol against english, from the transposition threshold, where english text stays below,
+// to the value where the text surely isn't english anymore.
+const ENGLISH_CHI_SQUARED: (f64, f64) = (analysis::ENGLISH_CHI_SQUARED_PER_SYMBOL, 0.6);
+// Index of coincidence between random (0) and english (1) above which a text keeps the english index.
+const MONOALPHABETIC_LEVEL: (f64, f64) = (0.5, 0.75);
+const RANDOM_LEVEL: (f64, f64) = (0.15, 0.45);
+const MIN_SYMBOLS: usize = 2;
+
+// 0 below `low`, 1 above `high` and linear in between.
+fn ramp(value: f64, (low, high): (f64, f64)) -> f64 {
+    ((value - low) / (high - low)).clamp(0.0, 1.0)
+}
+
+// Where the index of coincidence is between random text (0) and english (1).
+fn coincidence_level(index: f64) -> f64 {
+    let random = 1.0 / Alphabet::uppercase().len() as f64;
+    (index - random) / (Language::English.index_of_coincidence() - random)
+}
+
+// Chi-squared of the best caesar shift suggested by frequency analysis.
+fn best_caesar_shift(text: &str) -> Option<(usize, f64)> {
+    caesar_cipher::crack_by_frequency(text)
+        .into_iter()
+        .next()
+        .map(|(shift, _, plain_text)| {
+            (
+                shift,
+                analysis::chi_squared_per_symbol(&plain_text, Language::English),
+            )
+        })
+}
+
+// Returns the hypotheses from the most to the least likely.
+pub fn identify(cipher_text: &str) -> Vec<Hypothesis> {
+    let alphabet = Alphabet::uppercase();
+    let symbols = analysis::symbols(cipher_text, &alphabet);
+    if symbols.len() < MIN_SYMBOLS {
+        return vec![];
+    }
+
+    let level = coincidence_level(analysis::coincidence(&symbols, alphabet.len()));
+    let chi_squared = analysis::chi_squared_per_symbol(cipher_text, Language::English);
+    let monoalphabetic = ramp(level, MONOALPHABETIC_LEVEL);
+    // below the threshold of `detect` the frequencies are english, above it they fade out
+    let english = match transposition_cipher::detect(cipher_text) {
+        Family::Transposition => 1.0,
+        Family::Substitution => 1.0 - ramp(chi_squared, ENGLISH_CHI_SQUARED),
+    };
+
+    let mut hypotheses = vec![];
+
+    hypotheses.push(Hypothesis {
+        family: CipherFamily::Transposition,
+        confidence: monoalphabetic * english,
+        evidence: format!(
+            "coincidence level {:.2}, chi-squared per symbol {:.2}",
+            level, chi_squared
+        ),
+    });
+
+    let (shift, caesar_chi_squared) = best_caesar_shift(cipher_text).unwrap_or((0, f64::INFINITY));
+    let caesar = 1.0 - ramp(caesar_chi_squared, ENGLISH_CHI_SQUARED);
+    hypotheses.push(Hypothesis {
+        family: CipherFamily::Monoalphabetic,
+        // a general substitution is still monoalphabetic, caesar only makes it more certain
+        confidence: monoalphabetic * (1.0 - english) * (0.7 + 0.3 * caesar),
+        evidence: if caesar > 0.0 {
+            format!(
+                "coincidence level {:.2}, caesar shift {} fits english",
+                level, shift
+            )
+        } else {
+            format!(
+                "coincidence level {:.2}, no caesar shift fits english",
+                level
+            )
+        },
+    });
+
+    let period = vigenere_cipher::estimate_secret_length(&symbols)
+        .into_iter()
+        .find(|period| *period > 1);
+    if let Some(period) = period {
+        let periodic_level = coincidence_level(analysis::periodic_coincidence(
+            &symbols,
+            alphabet.len(),
+            period,
+        ));
+        hypotheses.push(Hypothesis {
+            family: CipherFamily::Polyalphabetic { period },
+            confidence: ramp(periodic_level, MONOALPHABETIC_LEVEL) * (1.0 - monoalphabetic),
+            evidence: format!(
+                "coincidence level {:.2}, {:.2} with period {}",
+                level, periodic_level, period
+            ),
+        });
+    }
+
+    // the space is the first symbol of the alphabet
+    let letters_only = symbols.iter().all(|s| *s != 0);
+    let even = symbols.len().is_multiple_of(2);
+    let doubled = symbols
+        .chunks(2)
+        .any(|pair| pair.len() == 2 && pair[0] == pair[1]);
+    let has_j = cipher_text.chars().any(|c| c.eq_ignore_ascii_case(&'J'));
+    let digraphic = if letters_only && even && !doubled {
+        // chance of random letters having no doubled pair at all
+        let chance = (25.0f64 / 26.0).powi(symbols.len() as i32 / 2);
+        (1.0 - chance) * if has_j { 0.3 } else { 1.0 } * (1.0 - monoalphabetic)
+    } else {
+        0.0
+    };
+    hypotheses.push(Hypothesis {
+        family: CipherFamily::Digraphic,
+        confidence: digraphic,
+        evidence: format!(
+            "{} letters, {}, {}, {}",
+            symbols.len(),
+            if even { "even length" } else { "odd length" },
+            if doubled {
+                "doubled letters in pairs"
+            } else {
+                "no doubled letters in pairs"
+            },
+            if has_j { "has J" } else { "no J" }
+        ),
+    });
+
+    let polyalphabetic = hypotheses
+        .iter()
+        .filter(|h| matches!(h.family, CipherFamily::Polyalphabetic { .. }))
+        .map(|h| h.confidence)
+        .fold(0.0, f64::max);
+    hypotheses.push(Hypothesis {
+        family: CipherFamily::Random,
+        confidence: (1.0 - ramp(level, RANDOM_LEVEL)) * (1.0 - polyalphabetic) * (1.0 - digraphic),
+        evidence: format!(
+            "coincidence level {:.2}, entropy {:.2} bits",
+            level,
+            analysis::entropy(cipher_text, &alphabet)
+        ),
+    });
+
+    hypotheses.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    hypotheses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symmetric::{
+        affine_cipher, playfair_cipher, substitution_cipher, transposition_cipher,
+    };
+
+    const MESSAGE: &str = "IT IS A TRUTH UNIVERSALLY ACKNOWLEDGED THAT A SINGLE MAN IN POSSESSION OF A GOOD \
+        FORTUNE MUST BE IN WANT OF A WIFE HOWEVER LITTLE KNOWN THE FEELINGS OR VIEWS OF SUCH A MAN \
+        MAY BE ON HIS FIRST ENTERING A NEIGHBOURHOOD THIS TRUTH IS SO WELL FIXED IN THE MINDS OF THE \
+        SURROUNDING FAMILIES THAT HE IS CONSIDERED THE RIGHTFUL PROPERTY OF SOME ONE OR OTHER OF THEIR DAUGHTERS";
+
+    fn best(cipher_text: &str) -> CipherFamily {
+        identify(cipher_text)[0].family
+    }
+
+    #[test]
+    fn identify_families() {
+        assert_eq!(
+            best(&transposition_cipher::columnar_encrypt(MESSAGE, "ZEBRA").unwrap()),
+            CipherFamily::Transposition
+        );
+        assert_eq!(
+            best(&caesar_cipher::encrypt(MESSAGE, 7).unwrap()),
+            CipherFamily::Monoalphabetic
+        );
+        assert_eq!(
+            best(&affine_cipher::encrypt(MESSAGE, (5, 8)).unwrap()),
+            CipherFamily::Monoalphabetic
+        );
+        assert_eq!(
+            best(&substitution_cipher::encrypt(MESSAGE, "ZEBRAS").unwrap()),
+            CipherFamily::Monoalphabetic
+        );
+        assert_eq!(
+            best(&vigenere_cipher::encrypt(MESSAGE, "LEMON").unwrap()),
+            CipherFamily::Polyalphabetic { period: 5 }
+        );
+        assert_eq!(
+            best(&playfair_cipher::encrypt(MESSAGE, "PLAYFAIR EXAMPLE").unwrap()),
+            CipherFamily::Digraphic
+        );
+
+        // a fixed pseudo random sequence keeps the test deterministic
+        let mut state = 7u64;
+        let random = (0..400)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (b'A' + ((state >> 33) % 26) as u8) as char
+            })
+            .collect::<String>();
+        assert_eq!(best(&random), CipherFamily::Random);
+    }
+
+    #[test]
+    fn ranked_hypotheses() {
+        let hypotheses = identify(&vigenere_cipher::encrypt(MESSAGE, "LEMON").unwrap());
+        assert!(hypotheses
+            .windows(2)
+            .all(|w| w[0].confidence >= w[1].confidence));
+        assert!(hypotheses
+            .iter()
+            .all(|h| (0.0..=1.0).contains(&h.confidence)));
+
+        let hypotheses = identify(&caesar_cipher::encrypt(MESSAGE, 7).unwrap());
+        assert!(hypotheses[0]
+            .evidence
+            .contains("caesar shift 7 fits english"));
+
+        assert_eq!(identify("A"), vec![]);
+    }
+}
@@ -0,0 +1,247 @@
+/*
+    Meet-in-the-middle attack against double encryption.
+
+    Encrypting twice with two independent DES keys looks like it should give 2 * 56 = 112 bits of security:
+
+    cipher = E(K2, E(K1, plain))
+
+    But with one known pair of plain and cipher text, the value in the middle can be reached from both sides:
+
+    -> Forward: encrypt the plain text with every K1 and keep E(K1, plain) -> K1 in a table.
+    -> Backward: decrypt the cipher text with every K2, when D(K2, cipher) is in the table,
+       (K1, K2) is a candidate, extra known pairs remove the false candidates.
+
+    The table is a vector of (middle value, K1) sorted by the middle value and searched with binary search,
+    16 bytes per entry and no allocation per entry, unlike a hash map of vectors.
+
+    That is 2 * 2^56 DES operations instead of 2^112, paid with a table of 2^56 entries,
+    the reason double DES was skipped and triple DES encrypts three times.
+
+    Time and memory tradeoff
+    When the table doesn't fit in memory, the K1 space is split in 2^(bits - table bits) parts,
+    each one needs a table of 2^(table bits) entries and a whole backward pass:
+
+    operations = 2^bits + 2^(2 * bits - table bits)
+
+    A table with every K1 gives the fastest attack, a table with a single entry is brute force again.
+
+    Running it against the 56 bits of DES isn't possible here, so the keys are reduced:
+    only the lowest `key_bits` effective bits of each key vary (the parity bits are skipped), the rest are zero.
+    Small tables quickly go back to brute force (24 key bits with a single entry table is 2^48 operations),
+    so attacks needing more than `MAX_OPERATIONS` are rejected before starting.
+*/
+
+use crate::error::CipherError;
+use crate::symmetric::data_encryption_standard::{process_block, sub_keys};
+use std::mem;
+use std::time::{Duration, Instant};
+
+pub const MAX_KEY_BITS: u32 = 24;
+// A few minutes in a release build.
+pub const MAX_OPERATIONS: u64 = 1 << 26;
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub key_bits: u32,
+    pub table_bits: u32,
+    // (K1, K2) pairs that encrypt every known plain text to its cipher text.
+    pub keys: Vec<(u64, u64)>,
+    // Forward and backward DES operations, checking the candidates isn't counted.
+    pub operations: u64,
+    pub table_entries: usize,
+    // Memory of the table, a middle value and a key per entry.
+    pub table_bytes: usize,
+    pub elapsed: Duration,
+}
+
+impl Report {
+    pub fn brute_force_operations(&self) -> u64 {
+        1 << (2 * self.key_bits)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tradeoff {
+    pub table_bits: u32,
+    pub operations: u64,
+    pub table_entries: u64,
+}
+
+// The DES key with the bits of `index` in its lowest effective bits, the last bit of each byte is parity.
+pub fn reduced_key(index: u64, key_bits: u32) -> u64 {
+    (0..key_bits)
+        .filter(|bit| index & (1 << bit) != 0)
+        .fold(0, |key, bit| key | 1 << ((bit / 7) * 8 + bit % 7 + 1))
+}
+
+pub fn double_encrypt(block: u64, k1: u64, k2: u64) -> u64 {
+    process_block(
+        process_block(block, &sub_keys(k1), false),
+        &sub_keys(k2),
+        false,
+    )
+}
+
+pub fn check_bits(key_bits: u32, table_bits: u32) -> Result<(), CipherError> {
+    if key_bits == 0 || key_bits > MAX_KEY_BITS {
+        return Err(CipherError::InvalidKey(
+            "the reduced key space must have between 1 and 24 bits",
+        ));
+    }
+    if table_bits > key_bits {
+        return Err(CipherError::InvalidKey(
+            "the table can't have more bits than the key space",
+        ));
+    }
+    let operations = operations(key_bits, table_bits);
+    if operations > MAX_OPERATIONS {
+        return Err(CipherError::TooMuchWork {
+            operations,
+            limit: MAX_OPERATIONS,
+        });
+    }
+    Ok(())
+}
+
+fn operations(key_bits: u32, table_bits: u32) -> u64 {
+    (1 << key_bits) + (1 << (2 * key_bits - table_bits))
+}
+
+// Finds the key pairs of a double encryption from known (plain, cipher) blocks, the first one is matched in the middle.
+pub fn attack(
+    known: (u64, u64),
+    checks: &[(u64, u64)],
+    key_bits: u32,
+    table_bits: u32,
+) -> Result<Report, CipherError> {
+    check_bits(key_bits, table_bits)?;
+    let start = Instant::now();
+    let (plain_text, cipher_text) = known;
+    let table_size = 1u64 << table_bits;
+
+    let mut keys = vec![];
+    let mut operations = 0;
+    let mut table: Vec<(u64, u64)> = Vec::with_capacity(table_size as usize);
+    for part in (0..1u64 << key_bits).step_by(table_size as usize) {
+        table.clear();
+        for index in part..part + table_size {
+            let k1 = reduced_key(index, key_bits);
+            table.push((process_block(plain_text, &sub_keys(k1), false), k1));
+        }
+        table.sort_unstable();
+        operations += table_size;
+
+        for index in 0..1u64 << key_bits {
+            let k2 = reduced_key(index, key_bits);
+            let middle = process_block(cipher_text, &sub_keys(k2), true);
+            let start = table.partition_point(|(value, _)| *value < middle);
+            let matches = table[start..]
+                .iter()
+                .take_while(|(value, _)| *value == middle)
+                .map(|(_, k1)| k1);
+            for k1 in matches {
+                if checks
+                    .iter()
+                    .all(|(plain, cipher)| double_encrypt(*plain, *k1, k2) == *cipher)
+                {
+                    keys.push((*k1, k2));
+                }
+            }
+        }
+        operations += 1 << key_bits;
+    }
+
+    Ok(Report {
+        key_bits,
+        table_bits,
+        keys,
+        operations,
+        table_entries: table.len(),
+        table_bytes: table.capacity() * mem::size_of::<(u64, u64)>(),
+        elapsed: start.elapsed(),
+    })
+}
+
+// Expected cost of the attack for every table size, from brute force to a table with the whole key space.
+pub fn tradeoffs(key_bits: u32) -> Vec<Tradeoff> {
+    (0..=key_bits)
+        .map(|table_bits| Tradeoff {
+            table_bits,
+            operations: operations(key_bits, table_bits),
+            table_entries: 1 << table_bits,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symmetric::data_encryption_standard::{decrypt_block, encrypt_block};
+
+    const KEY_BITS: u32 = 8;
+    const PLAIN_TEXTS: [u64; 2] = [0x4E6F772069732074, 0x68652074696D6520];
+
+    #[test]
+    fn reduced_keys() {
+        assert_eq!(reduced_key(0, KEY_BITS), 0);
+        assert_eq!(reduced_key(0b1, KEY_BITS), 0b10);
+        // the 8th bit skips the parity bit and goes to the next byte
+        assert_eq!(reduced_key(0b1000_0001, KEY_BITS), 0x0202);
+        assert_eq!(reduced_key(0xFF, 4), 0x1E);
+
+        let k1 = reduced_key(0x5A, KEY_BITS);
+        let k2 = reduced_key(0xC3, KEY_BITS);
+        assert_eq!(
+            double_encrypt(PLAIN_TEXTS[0], k1, k2),
+            encrypt_block(encrypt_block(PLAIN_TEXTS[0], k1), k2)
+        );
+        assert_eq!(
+            decrypt_block(
+                decrypt_block(double_encrypt(PLAIN_TEXTS[0], k1, k2), k2),
+                k1
+            ),
+            PLAIN_TEXTS[0]
+        );
+    }
+
+    #[test]
+    fn meet_in_the_middle() {
+        let (k1, k2) = (reduced_key(0x5A, KEY_BITS), reduced_key(0xC3, KEY_BITS));
+        let [known, check] = PLAIN_TEXTS.map(|plain| (plain, double_encrypt(plain, k1, k2)));
+
+        let report = attack(known, &[check], KEY_BITS, KEY_BITS).unwrap();
+        assert_eq!(report.keys, vec![(k1, k2)]);
+        assert_eq!(report.table_entries, 1 << KEY_BITS);
+        assert_eq!(report.table_bytes, (1 << KEY_BITS) * 16);
+        assert!(report.operations < report.brute_force_operations());
+
+        // a smaller table finds the same keys, paying with more operations
+        let costs = tradeoffs(KEY_BITS);
+        for table_bits in [KEY_BITS, 5, 2] {
+            let report = attack(known, &[check], KEY_BITS, table_bits).unwrap();
+            assert_eq!(report.keys, vec![(k1, k2)]);
+            assert_eq!(report.table_entries, 1 << table_bits);
+            assert_eq!(report.operations, costs[table_bits as usize].operations);
+        }
+        assert_eq!(costs[0].operations, (1 << KEY_BITS) + (1 << (2 * KEY_BITS)));
+    }
+
+    #[test]
+    fn invalid_bits() {
+        let known = (PLAIN_TEXTS[0], PLAIN_TEXTS[1]);
+        assert!(attack(known, &[], 0, 0).is_err());
+        assert!(attack(known, &[], MAX_KEY_BITS + 1, 1).is_err());
+        assert_eq!(
+            attack(known, &[], 4, 5).unwrap_err(),
+            CipherError::InvalidKey("the table can't have more bits than the key space")
+        );
+        assert_eq!(
+            check_bits(MAX_KEY_BITS, 0),
+            Err(CipherError::TooMuchWork {
+                operations: (1 << 24) + (1 << 48),
+                limit: MAX_OPERATIONS
+            })
+        );
+        assert!(check_bits(MAX_KEY_BITS, MAX_KEY_BITS - 1).is_ok());
+    }
+}
@@ -41,6 +41,8 @@
     a * x + b * y = gcd(a, b);
 */
 
+use crate::cipher::{Cipher, KeyGen};
+use crate::error::CipherError;
 use num_bigint::BigUint;
 use num_traits::ToPrimitive;
 use rand::Rng;
@@ -48,15 +50,21 @@ use rand::Rng;
 const START: i32 = 100;
 const END: i32 = 1000;
 
-fn euclidean_gcd(a: u128, b: u128) -> u128 {
-    if a % b == 0 {
+// Every encrypted block is smaller than n, so it always fits in the bytes of a u128.
+const BLOCK_LENGTH: usize = 16;
+
+// (private key, public key), each one being (exponent, n).
+type KeyPair = ((u128, u128), (u128, u128));
+
+pub fn euclidean_gcd(a: u128, b: u128) -> u128 {
+    if a.is_multiple_of(b) {
         return b;
     };
 
     euclidean_gcd(b, a % b)
 }
 
-fn modular_inverse(a: i128, b: i128) -> (i128, i128, i128) {
+pub fn modular_inverse(a: i128, b: i128) -> (i128, i128, i128) {
     if a == 0 {
         return (b, 0, 1);
     };
@@ -71,12 +79,12 @@ fn modular_inverse(a: i128, b: i128) -> (i128, i128, i128) {
 
 // a is always smaller value
 fn extended_gcd(a: u128, b: u128) -> (u128, u128, u128) {
-    return if b == 0 {
+    if b == 0 {
         (a, 1, 0)
     } else {
         let (gcd, x, y) = extended_gcd(b, a % b);
         (gcd, y, x - (a / b) * y)
-    };
+    }
 }
 
 fn is_prime(num: i128) -> bool {
@@ -110,9 +118,12 @@ fn generate_large_prime_number() -> u128 {
     num
 }
 
-fn generate_rsa_keys() -> ((u128, u128), (u128, u128)) {
+fn generate_rsa_keys() -> KeyPair {
     let p = generate_large_prime_number();
-    let q = generate_large_prime_number();
+    let mut q = generate_large_prime_number();
+    while q == p {
+        q = generate_large_prime_number();
+    }
 
     // trapdoor function, calculating n is fast but the opposite (getting p an q from n) is an exponentially slow operation.
     let n = p * q;
@@ -129,16 +140,18 @@ fn generate_rsa_keys() -> ((u128, u128), (u128, u128)) {
         public_key = rng.gen_range(1..=phi);
     }
 
-    // private key is the modular inverse of the public key
-    let private_key = modular_inverse(public_key as i128, phi as i128).1 as u128;
+    // private key is the modular inverse of the public key, the coefficient may be negative
+    let private_key = modular_inverse(public_key as i128, phi as i128)
+        .1
+        .rem_euclid(phi as i128) as u128;
 
     ((private_key, n), (public_key, n))
 }
 
-fn encrypt(public_key: (u128, u128), message: &str) -> Vec<Vec<u8>> {
+fn encrypt(public_key: (u128, u128), message: &[u8]) -> Vec<Vec<u8>> {
     let mut result = vec![];
     let (public_key, n) = public_key;
-    for &c in message.as_bytes() {
+    for &c in message {
         let cipher = BigUint::from(c).modpow(&BigUint::from(public_key), &BigUint::from(n));
         result.push(cipher.to_bytes_le());
     }
@@ -146,20 +159,62 @@ fn encrypt(public_key: (u128, u128), message: &str) -> Vec<Vec<u8>> {
     result
 }
 
-fn decrypt(private_key: (u128, u128), message: &Vec<Vec<u8>>) -> String {
-    let mut result = String::new();
+fn decrypt(private_key: (u128, u128), message: &[Vec<u8>]) -> Result<Vec<u8>, CipherError> {
+    let mut result = vec![];
 
     let (private_key, n) = private_key;
     for num in message.iter() {
         let t = BigUint::from_bytes_le(num);
+        if t >= BigUint::from(n) {
+            return Err(CipherError::InvalidBlock);
+        }
+
         let a = t.modpow(&BigUint::from(private_key), &BigUint::from(n));
 
-        if let Some(v) = a.to_u128() {
-            let c = char::from(v as u8);
-            result.push(c);
+        // every block was encrypted from a single byte, anything bigger means a wrong key or corrupted data
+        let Some(v) = a.to_u8() else {
+            return Err(CipherError::InvalidBlock);
         };
+        result.push(v);
+    }
+
+    Ok(result)
+}
+
+pub struct Rsa;
+
+impl Cipher for Rsa {
+    type Key = KeyPair;
+
+    fn encrypt(&self, key: &KeyPair, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        // every block is padded to the same length so the cipher text can be split again
+        Ok(encrypt(key.1, plain_text)
+            .into_iter()
+            .flat_map(|mut block| {
+                block.resize(BLOCK_LENGTH, 0);
+                block
+            })
+            .collect())
+    }
+
+    fn decrypt(&self, key: &KeyPair, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        if !cipher_text.len().is_multiple_of(BLOCK_LENGTH) {
+            return Err(CipherError::InvalidEncoding);
+        }
+
+        let blocks = cipher_text
+            .chunks(BLOCK_LENGTH)
+            .map(|block| block.to_vec())
+            .collect::<Vec<Vec<u8>>>();
+
+        decrypt(key.0, &blocks)
+    }
+}
+
+impl KeyGen for Rsa {
+    fn generate_key(&self) -> KeyPair {
+        generate_rsa_keys()
     }
-    result
 }
 
 #[cfg(test)]
@@ -192,10 +247,50 @@ mod tests {
         let result = generate_rsa_keys();
 
         let message = "Hello World";
-        let cipher = encrypt(result.1, message);
+        let cipher = encrypt(result.1, message.as_bytes());
         assert_eq!(cipher.len(), message.len());
 
-        let plain_text = decrypt(result.0, &cipher);
-        assert_eq!(plain_text.as_bytes(), message.as_bytes());
+        let plain_text = decrypt(result.0, &cipher).unwrap();
+        assert_eq!(plain_text, message.as_bytes());
+    }
+
+    #[test]
+    fn encrypt_decrypt_through_traits() {
+        let keys = Rsa.generate_key();
+        let cipher = Rsa.encrypt(&keys, b"Hello World").unwrap();
+        assert_eq!(cipher.len(), 11 * BLOCK_LENGTH);
+        assert_eq!(Rsa.decrypt(&keys, &cipher).unwrap(), b"Hello World");
+
+        // any bytes round-trip, not only utf-8 text
+        let cipher = Rsa.encrypt(&keys, &[0x00, 0xff, 0x80]).unwrap();
+        assert_eq!(Rsa.decrypt(&keys, &cipher).unwrap(), [0x00, 0xff, 0x80]);
+        assert_eq!(
+            Rsa.decrypt(&keys, &cipher[1..]),
+            Err(CipherError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn decrypt_invalid_blocks() {
+        // p = 61, q = 53, e = 17, d = 2753
+        let private_key = (2753, 3233);
+        let public_key = (17, 3233);
+
+        let cipher = encrypt(public_key, "Olá".as_bytes());
+        assert_eq!(decrypt(private_key, &cipher).unwrap(), "Olá".as_bytes());
+
+        let out_of_range = vec![BigUint::from(5000u32).to_bytes_le()];
+        assert_eq!(
+            decrypt(private_key, &out_of_range),
+            Err(CipherError::InvalidBlock)
+        );
+
+        let not_a_byte = vec![BigUint::from(300u32)
+            .modpow(&BigUint::from(17u32), &BigUint::from(3233u32))
+            .to_bytes_le()];
+        assert_eq!(
+            decrypt(private_key, &not_a_byte),
+            Err(CipherError::InvalidBlock)
+        );
     }
 }
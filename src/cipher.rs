@@ -0,0 +1,48 @@
+/*
+    Common interface shared by the ciphers of the crate.
+
+    Every cipher encrypts and decrypts bytes with its own kind of key (a number for caesar,
+    a word for vigenere, a list of shifts for one time pad, bytes for AES and a key pair for RSA),
+    so tools can pick an algorithm without knowing the details of each module.
+
+    -> Cipher: key type plus encryption and decryption over bytes.
+    -> BlockCipher: ciphers that work on fixed size blocks, like AES with 16 bytes.
+    -> StreamCipher: classical ciphers that shift each symbol by the next value of a key stream,
+       caesar repeats the same shift, vigenere repeats the secret and one time pad never repeats it.
+    -> KeyGen: ciphers that know how to generate a random key.
+*/
+
+use crate::error::CipherError;
+
+pub trait Cipher {
+    type Key;
+
+    fn encrypt(&self, key: &Self::Key, plain_text: &[u8]) -> Result<Vec<u8>, CipherError>;
+
+    fn decrypt(&self, key: &Self::Key, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError>;
+}
+
+#[allow(dead_code)]
+pub trait BlockCipher: Cipher {
+    const BLOCK_SIZE: usize;
+
+    fn encrypt_block(&self, key: &Self::Key, block: &[u8]) -> Result<Vec<u8>, CipherError>;
+
+    fn decrypt_block(&self, key: &Self::Key, block: &[u8]) -> Result<Vec<u8>, CipherError>;
+}
+
+#[allow(dead_code)]
+pub trait StreamCipher: Cipher {
+    // Shifts applied to the first `length` symbols of the text.
+    fn key_stream(&self, key: &Self::Key, length: usize) -> Result<Vec<usize>, CipherError>;
+}
+
+#[allow(dead_code)]
+pub trait KeyGen: Cipher {
+    fn generate_key(&self) -> Self::Key;
+}
+
+// Classical ciphers work over text, so the bytes must be valid utf-8.
+pub fn to_text(data: &[u8]) -> Result<&str, CipherError> {
+    std::str::from_utf8(data).map_err(|_| CipherError::InvalidEncoding)
+}
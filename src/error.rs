@@ -0,0 +1,107 @@
+use std::fmt;
+
+/*
+    Errors shared by every cipher of the crate, so the CLI can report what went wrong
+    instead of panicking in the middle of an encryption.
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CipherError {
+    InvalidKey(&'static str),
+    InvalidKeyLength {
+        expected: usize,
+        actual: usize,
+    },
+    InvalidKeySymbol(char),
+    InvalidIvLength {
+        expected: usize,
+        actual: usize,
+    },
+    KeyTooShort {
+        required: usize,
+        available: usize,
+    },
+    InvalidInputSymbol(char),
+    EmptyAlphabet,
+    DuplicatedSymbol(char),
+    InvalidEncoding,
+    InvalidPadding,
+    InvalidBlock,
+    InvalidPadFile(&'static str),
+    ChecksumMismatch,
+    WeakKey,
+    SemiWeakKey {
+        pair: u64,
+    },
+    InvalidParity,
+    TooMuchWork {
+        operations: u64,
+        limit: u64,
+    },
+    // A named option (mode, format...) with a value that isn't one of the expected ones.
+    InvalidOption {
+        option: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for CipherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKey(reason) => write!(f, "invalid key: {}", reason),
+            Self::InvalidKeyLength { expected, actual } => write!(
+                f,
+                "invalid key length: expected {} bytes, got {}",
+                expected, actual
+            ),
+            Self::InvalidKeySymbol(c) => write!(f, "key symbol '{}' is not in the alphabet", c),
+            Self::InvalidIvLength { expected, actual } => write!(
+                f,
+                "invalid initialization vector length: expected {} bytes, got {}",
+                expected, actual
+            ),
+            Self::KeyTooShort {
+                required,
+                available,
+            } => write!(
+                f,
+                "key too short: {} symbols required, {} available",
+                required, available
+            ),
+            Self::InvalidInputSymbol(c) => write!(f, "symbol '{}' is not in the alphabet", c),
+            Self::EmptyAlphabet => write!(f, "the alphabet has no symbols"),
+            Self::DuplicatedSymbol(c) => write!(f, "symbol '{}' appears twice in the alphabet", c),
+            Self::InvalidEncoding => write!(f, "invalid encoding"),
+            Self::InvalidPadding => write!(f, "invalid padding"),
+            Self::InvalidBlock => write!(f, "invalid cipher text block"),
+            Self::InvalidPadFile(reason) => write!(f, "invalid pad file: {}", reason),
+            Self::ChecksumMismatch => write!(f, "checksum doesn't match, the data was modified"),
+            Self::WeakKey => write!(f, "weak key: encrypting twice gives the plain text back"),
+            Self::SemiWeakKey { pair } => write!(
+                f,
+                "semi-weak key: the key {:016x} decrypts what it encrypts",
+                pair
+            ),
+            Self::InvalidParity => write!(f, "every key byte must have an odd number of ones"),
+            Self::TooMuchWork { operations, limit } => write!(
+                f,
+                "too much work: {} operations needed, the limit is {}",
+                operations, limit
+            ),
+            Self::InvalidOption {
+                option,
+                value,
+                expected,
+            } => write!(f, "invalid {} '{}', expected {}", option, value, expected),
+        }
+    }
+}
+
+impl std::error::Error for CipherError {}
+
+impl From<CipherError> for String {
+    fn from(value: CipherError) -> Self {
+        value.to_string()
+    }
+}
@@ -1,9 +1,36 @@
-use clap::{Parser, Subcommand};
+use analysis::identify::identify;
+use analysis::meet_in_the_middle;
+use cipher::Cipher;
+use clap::{Args, Parser, Subcommand};
+use error::CipherError;
+use rand::Rng;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use symmetric::advanced_encryption_standard::{self, Aes};
+use symmetric::affine_cipher::{self, AffineCipher};
+use symmetric::alphabet::{Alphabet, UnknownSymbol};
+use symmetric::caesar_cipher::{self, CaesarCipher};
+use symmetric::data_encryption_standard::{self as des, Des, Parity};
+use symmetric::enigma::{self, Enigma, Reflector, Settings};
+use symmetric::fractionating_cipher::{Adfgvx, Bifid, Trifid};
+use symmetric::hill_cipher::{self, HillCipher};
+use symmetric::one_time_pad::{self, OneTimePad, Pad};
+use symmetric::playfair_cipher::PlayfairCipher;
+use symmetric::substitution_cipher::{self, SubstitutionCipher};
+use symmetric::trace::{self, TraceFormat};
+use symmetric::transposition_cipher::{self, ColumnarTransposition, RailFence};
+use symmetric::triple_data_encryption_standard::TripleDes;
+use symmetric::vigenere_cipher::{self, Mode, VigenereCipher};
 
+mod analysis;
 mod asymmetric;
+mod cipher;
+mod error;
 mod symmetric;
 
 const IV: &[u8; 16] = b"0000000000000000";
+const DES_IV: &[u8; 8] = b"00000000";
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -19,18 +46,155 @@ pub enum Encrypt {
         data: String,
         /// Secret number to shift in caesar cipher encryption method
         secret: i32,
+        /// Shift only letters keeping their case, punctuation and spaces are left untouched
+        #[arg(long)]
+        preserve_case: bool,
     },
     VigenereCipher {
         /// Data to be encrypted
         data: String,
         /// Secret word used to shift values in data in vigenere cipher method.
         secret: String,
+        /// Shift only letters keeping their case, the secret advances only on letters
+        #[arg(long)]
+        preserve_case: bool,
+        /// repeating, autokey, beaufort, variant-beaufort or running-key (the secret is a text at least as long as the data)
+        #[arg(long, default_value = "repeating")]
+        mode: Mode,
+    },
+    PlayfairCipher {
+        /// Data to be encrypted
+        data: String,
+        /// Keyword used to build the 5x5 key square, J is merged with I
+        secret: String,
+        /// Letter inserted between repeated letters and at the end of odd length texts
+        #[arg(long, default_value_t = 'X')]
+        filler: char,
+    },
+    HillCipher {
+        /// Data to be encrypted
+        data: String,
+        /// Key matrix written row by row, its length must be a perfect square (4 for 2x2, 9 for 3x3...)
+        secret: String,
+    },
+    AffineCipher {
+        /// Data to be encrypted
+        data: String,
+        /// Multiplier of the secret, it must be coprime with the length of the alphabet
+        a: i32,
+        /// Shift of the secret
+        b: i32,
+        /// Transform only letters keeping their case, punctuation and spaces are left untouched
+        #[arg(long)]
+        preserve_case: bool,
+    },
+    SubstitutionCipher {
+        /// Data to be encrypted
+        data: String,
+        /// Keyword or full keyed alphabet, every symbol is replaced by the one in the same position
+        secret: String,
+        /// Replace only letters keeping their case, punctuation and spaces are left untouched
+        #[arg(long)]
+        preserve_case: bool,
+    },
+    Enigma {
+        /// Data to be encrypted, only letters are encrypted
+        data: String,
+        /// Rotors from left to right, between I and VIII
+        #[arg(long, default_value = "I II III")]
+        rotors: String,
+        /// Reflector, B or C
+        #[arg(long, default_value = "B")]
+        reflector: String,
+        /// Ring settings from left to right, as letters (AAA) or numbers (1 1 1)
+        #[arg(long, default_value = "AAA")]
+        rings: String,
+        /// Starting positions of the rotors from left to right
+        #[arg(long, default_value = "AAA")]
+        positions: String,
+        /// Pairs of letters swapped by the plugboard, like "AV BS CG"
+        #[arg(long, default_value = "")]
+        plugboard: String,
+    },
+    Adfgvx {
+        /// Data to be encrypted, only letters and digits are kept
+        data: String,
+        /// Keyword used to build the 6x6 square of letters and digits
+        square: String,
+        /// Keyword of the columnar transposition
+        secret: String,
+    },
+    Bifid {
+        /// Data to be encrypted, only letters are kept and J is merged with I
+        data: String,
+        /// Keyword used to build the 5x5 square
+        secret: String,
+        /// Number of symbols fractionated together, 0 for the whole text
+        #[arg(long, default_value_t = 5)]
+        period: usize,
+    },
+    Trifid {
+        /// Data to be encrypted, only letters and spaces are kept
+        data: String,
+        /// Keyword used to build the 3x3x3 cube of letters and space
+        secret: String,
+        /// Number of symbols fractionated together, 0 for the whole text
+        #[arg(long, default_value_t = 5)]
+        period: usize,
+    },
+    RailFence {
+        /// Data to be encrypted, every character is moved
+        data: String,
+        /// Number of rails
+        rails: usize,
+    },
+    ColumnarTransposition {
+        /// Data to be encrypted, every character is moved
+        data: String,
+        /// Keyword, the columns are read in the alphabetical order of its letters
+        secret: String,
+        /// Keyword of a second transposition, making it a double columnar transposition
+        #[arg(long)]
+        second_secret: Option<String>,
     },
     Aes {
         /// Data to be encrypted
         data: String,
         /// 32 bit secret used to decrypt data
         secret: String,
+        /// Print every round of every block to stderr, as text or json
+        #[arg(long, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "text")]
+        trace: Option<TraceFormat>,
+    },
+    Des {
+        /// Data to be encrypted
+        data: String,
+        /// 8 byte secret, DES uses 56 bits of it
+        secret: String,
+        /// The secret is hex encoded, needed for keys that aren't printable
+        #[arg(long)]
+        hex_secret: bool,
+        /// Reject secrets whose bytes don't have odd parity instead of warning about them
+        #[arg(long)]
+        check_parity: bool,
+        /// Print every round of every block to stderr, as text or json
+        #[arg(long, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "text")]
+        trace: Option<TraceFormat>,
+    },
+    TripleDes {
+        /// Data to be encrypted
+        data: String,
+        /// 16 byte (two keys) or 24 byte (three keys) secret, consecutive keys must differ
+        secret: String,
+        /// The secret is hex encoded, needed for keys that aren't printable
+        #[arg(long)]
+        hex_secret: bool,
+    },
+    OneTimePad {
+        /// Data to be encrypted
+        data: String,
+        /// Pad file created with generate-pad, the used values are marked as consumed
+        pad: PathBuf,
     },
 }
 
@@ -41,18 +205,245 @@ pub enum Decrypt {
         data: String,
         /// Secret number to shift back in caesar cipher decryption method
         secret: i32,
+        /// Shift only letters keeping their case, punctuation and spaces are left untouched
+        #[arg(long)]
+        preserve_case: bool,
     },
     VigenereCipher {
         /// Data to be decrypted
         data: String,
         /// Secret word used to shift values in data in vigenere cipher method.
         secret: String,
+        /// Shift only letters keeping their case, the secret advances only on letters
+        #[arg(long)]
+        preserve_case: bool,
+        /// repeating, autokey, beaufort, variant-beaufort or running-key (the secret is a text at least as long as the data)
+        #[arg(long, default_value = "repeating")]
+        mode: Mode,
+    },
+    PlayfairCipher {
+        /// Data to be decrypted
+        data: String,
+        /// Keyword used to build the 5x5 key square, J is merged with I
+        secret: String,
+        /// Letter removed from the decrypted message
+        #[arg(long, default_value_t = 'X')]
+        filler: char,
+    },
+    HillCipher {
+        /// Data to be decrypted
+        data: String,
+        /// Key matrix written row by row, its length must be a perfect square (4 for 2x2, 9 for 3x3...)
+        secret: String,
+    },
+    AffineCipher {
+        /// Data to be decrypted
+        data: String,
+        /// Multiplier of the secret, it must be coprime with the length of the alphabet
+        a: i32,
+        /// Shift of the secret
+        b: i32,
+        /// Transform only letters keeping their case, punctuation and spaces are left untouched
+        #[arg(long)]
+        preserve_case: bool,
+    },
+    SubstitutionCipher {
+        /// Data to be decrypted
+        data: String,
+        /// Keyword or full keyed alphabet, every symbol is replaced by the one in the same position
+        secret: String,
+        /// Replace only letters keeping their case, punctuation and spaces are left untouched
+        #[arg(long)]
+        preserve_case: bool,
+    },
+    Enigma {
+        /// Data to be decrypted, only letters are decrypted
+        data: String,
+        /// Rotors from left to right, between I and VIII
+        #[arg(long, default_value = "I II III")]
+        rotors: String,
+        /// Reflector, B or C
+        #[arg(long, default_value = "B")]
+        reflector: String,
+        /// Ring settings from left to right, as letters (AAA) or numbers (1 1 1)
+        #[arg(long, default_value = "AAA")]
+        rings: String,
+        /// Starting positions of the rotors from left to right
+        #[arg(long, default_value = "AAA")]
+        positions: String,
+        /// Pairs of letters swapped by the plugboard, like "AV BS CG"
+        #[arg(long, default_value = "")]
+        plugboard: String,
+    },
+    Adfgvx {
+        /// Data to be decrypted, only the labels A, D, F, G, V and X (in any case) and whitespace are accepted
+        data: String,
+        /// Keyword used to build the 6x6 square of letters and digits
+        square: String,
+        /// Keyword of the columnar transposition
+        secret: String,
+    },
+    Bifid {
+        /// Data to be decrypted, only letters are kept and J is merged with I
+        data: String,
+        /// Keyword used to build the 5x5 square
+        secret: String,
+        /// Number of symbols fractionated together, 0 for the whole text
+        #[arg(long, default_value_t = 5)]
+        period: usize,
+    },
+    Trifid {
+        /// Data to be decrypted, only letters and spaces are kept
+        data: String,
+        /// Keyword used to build the 3x3x3 cube of letters and space
+        secret: String,
+        /// Number of symbols fractionated together, 0 for the whole text
+        #[arg(long, default_value_t = 5)]
+        period: usize,
+    },
+    RailFence {
+        /// Data to be decrypted, every character is moved
+        data: String,
+        /// Number of rails
+        rails: usize,
+    },
+    ColumnarTransposition {
+        /// Data to be decrypted, every character is moved
+        data: String,
+        /// Keyword, the columns are read in the alphabetical order of its letters
+        secret: String,
+        /// Keyword of a second transposition, making it a double columnar transposition
+        #[arg(long)]
+        second_secret: Option<String>,
     },
     Aes {
         /// Data to be decrypted
         data: String,
         /// 32 bit secret used to decrypt data
         secret: String,
+        /// Print every inverse round of every block to stderr, as text or json
+        #[arg(long, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "text")]
+        trace: Option<TraceFormat>,
+    },
+    Des {
+        /// Data to be decrypted
+        data: String,
+        /// 8 byte secret, DES uses 56 bits of it
+        secret: String,
+        /// The secret is hex encoded, needed for keys that aren't printable
+        #[arg(long)]
+        hex_secret: bool,
+        /// Reject secrets whose bytes don't have odd parity instead of warning about them
+        #[arg(long)]
+        check_parity: bool,
+        /// Print every round of every block to stderr, as text or json
+        #[arg(long, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "text")]
+        trace: Option<TraceFormat>,
+    },
+    TripleDes {
+        /// Data to be decrypted
+        data: String,
+        /// 16 byte (two keys) or 24 byte (three keys) secret, consecutive keys must differ
+        secret: String,
+        /// The secret is hex encoded, needed for keys that aren't printable
+        #[arg(long)]
+        hex_secret: bool,
+    },
+    OneTimePad {
+        /// Data to be decrypted
+        data: String,
+        /// Copy of the pad file used to encrypt the data
+        pad: PathBuf,
+        /// Pad offset printed when the data was encrypted
+        offset: usize,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct CrackInput {
+    /// Cipher text to be cracked
+    #[arg(required_unless_present = "file")]
+    data: Option<String>,
+    /// Read the cipher text from a file instead
+    #[arg(long, conflicts_with = "data")]
+    file: Option<PathBuf>,
+    /// Number of candidates to show
+    #[arg(long, default_value_t = 5)]
+    top: usize,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Crack {
+    CaesarCipher {
+        #[command(flatten)]
+        input: CrackInput,
+        /// Try only the secrets suggested by the most frequent letter instead of every secret
+        #[arg(long)]
+        frequency_analysis: bool,
+    },
+    VigenereCipher {
+        #[command(flatten)]
+        input: CrackInput,
+        /// The cipher text was encrypted with the autokey mode, the primer is searched instead
+        #[arg(long)]
+        autokey: bool,
+    },
+    AffineCipher {
+        #[command(flatten)]
+        input: CrackInput,
+    },
+    SubstitutionCipher {
+        #[command(flatten)]
+        input: CrackInput,
+    },
+    RailFence {
+        #[command(flatten)]
+        input: CrackInput,
+    },
+    ColumnarTransposition {
+        #[command(flatten)]
+        input: CrackInput,
+    },
+    /// Two cipher texts encrypted with the same one time pad values, both plain texts are recovered
+    TwoTimePad {
+        #[command(flatten)]
+        input: CrackInput,
+        /// Second cipher text, encrypted with the same values as the first one
+        #[arg(long, required_unless_present = "second_file")]
+        second: Option<String>,
+        /// Read the second cipher text from a file instead
+        #[arg(long, conflicts_with = "second")]
+        second_file: Option<PathBuf>,
+        /// Slide this word over both messages instead of recovering the whole texts
+        #[arg(long)]
+        crib: Option<String>,
+    },
+    /// Search the enigma settings with the bombe, from a word expected in the message
+    Enigma {
+        #[command(flatten)]
+        input: CrackInput,
+        /// Plain text expected in the message, like WETTERBERICHT
+        #[arg(long)]
+        crib: String,
+        /// Letter of the cipher text where the crib starts, every position the crib may be is tried when missing
+        #[arg(long)]
+        offset: Option<usize>,
+        /// Rotors the machine may use, every order of 3 of them is tried
+        #[arg(long, default_value = "I II III")]
+        rotors: String,
+        /// Reflector, B or C
+        #[arg(long, default_value = "B")]
+        reflector: Reflector,
+    },
+    /// Recover the secret of a hill cipher from a known plain text and its cipher text
+    HillCipher {
+        /// Known plain text, it needs at least as many blocks as the size of the secret matrix
+        plain_text: String,
+        /// Cipher text of the known plain text
+        cipher_text: String,
+        /// Number of rows and columns of the secret matrix
+        #[arg(long, default_value_t = 2)]
+        size: usize,
     },
 }
 
@@ -69,45 +460,629 @@ enum Command {
         #[command(subcommand)]
         command: Decrypt,
     },
+
+    /// Recover the secret of a cipher text without knowing it, candidates are ranked by how much they look like english
+    Crack {
+        #[command(subcommand)]
+        command: Crack,
+    },
+
+    /// Guess which family of classical ciphers produced the data, the most likely first
+    Identify {
+        /// Cipher text to be analysed
+        data: String,
+    },
+
+    /// Break a double DES encryption with random reduced keys, showing why double encryption doesn't double the key size
+    MeetInTheMiddle {
+        /// Effective bits of each DES key, the other bits are zero
+        #[arg(long, default_value_t = 16)]
+        key_bits: u32,
+        /// Bits of the forward table, smaller tables use less memory but more operations, defaults to the key bits.
+        /// Attacks needing more than 2^26 DES operations are rejected
+        #[arg(long)]
+        table_bits: Option<u32>,
+    },
+
+    /// Generate a random one time pad file, it must be shared securely with the receiver
+    GeneratePad {
+        /// File to be created, existing files are never overwritten
+        path: PathBuf,
+        /// Number of values in the pad, one is consumed per encrypted symbol
+        length: usize,
+        /// uppercase, letters, printable-ascii, bytes or a custom list of symbols
+        #[arg(long, default_value = "uppercase")]
+        alphabet: Alphabet,
+    },
+}
+
+// DES ignores the parity bits, so keys without odd parity still work, but they are reported with the fixed key.
+fn des_secret(secret: String, hex_secret: bool, parity: Parity) -> Result<Vec<u8>, CipherError> {
+    let secret = if hex_secret {
+        hex::decode(secret).map_err(|_| CipherError::InvalidEncoding)?
+    } else {
+        secret.into_bytes()
+    };
+    let keys = secret
+        .chunks(des::KEY_LENGTH)
+        .map(des::to_u64)
+        .collect::<Vec<u64>>();
+    if parity == Parity::Ignore
+        && secret.len().is_multiple_of(des::KEY_LENGTH)
+        && keys.iter().any(|key| !des::has_odd_parity(*key))
+    {
+        let fixed = keys
+            .iter()
+            .map(|key| format!("{:016x}", des::fix_parity(*key)))
+            .collect::<String>();
+        eprintln!(
+            "Warning: the secret doesn't have odd parity, DES ignores those bits, with fixed parity it is {} (use --hex-secret)",
+            fixed
+        );
+    }
+    Ok(secret)
+}
+
+// Classical ciphers produce text while modern ones produce raw bytes that are shown as hex.
+#[derive(Debug, Clone, Copy)]
+enum Encoding {
+    Text,
+    Hex,
+}
+
+fn encrypt<C: Cipher>(
+    cipher: &C,
+    key: &C::Key,
+    data: &str,
+    encoding: Encoding,
+) -> Result<String, CipherError> {
+    let encrypted = cipher.encrypt(key, data.as_bytes())?;
+    match encoding {
+        Encoding::Text => String::from_utf8(encrypted).map_err(|_| CipherError::InvalidEncoding),
+        Encoding::Hex => Ok(hex::encode(encrypted)),
+    }
+}
+
+fn decrypt<C: Cipher>(
+    cipher: &C,
+    key: &C::Key,
+    data: &str,
+    encoding: Encoding,
+) -> Result<String, CipherError> {
+    let cipher_text = match encoding {
+        Encoding::Text => data.as_bytes().to_vec(),
+        Encoding::Hex => hex::decode(data).map_err(|_| CipherError::InvalidEncoding)?,
+    };
+    let decrypted = cipher.decrypt(key, &cipher_text)?;
+    String::from_utf8(decrypted).map_err(|_| CipherError::InvalidEncoding)
+}
+
+fn alphabet(preserve_case: bool) -> Alphabet {
+    if preserve_case {
+        Alphabet::letters()
+    } else {
+        Alphabet::uppercase()
+    }
+}
+
+fn read_pad(path: &Path) -> Result<Pad, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Ok(content.parse::<Pad>()?)
+}
+
+fn write_pad(path: &Path, pad: &Pad) -> Result<(), String> {
+    fs::write(path, pad.to_string()).map_err(|e| e.to_string())
+}
+
+impl CrackInput {
+    fn cipher_text(&self) -> Result<String, String> {
+        match (&self.data, &self.file) {
+            (_, Some(path)) => fs::read_to_string(path)
+                .map(|content| content.trim_end().to_string())
+                .map_err(|e| format!("{}: {}", path.display(), e)),
+            (Some(data), None) => Ok(data.clone()),
+            (None, None) => Err("the cipher text or --file is required".to_string()),
+        }
+    }
+}
+
+fn print_candidates<K: std::fmt::Display>(candidates: Vec<(K, f64, String)>, top: usize) {
+    if candidates.is_empty() {
+        println!("No candidates found");
+    }
+    for (i, (secret, score, plain_text)) in candidates.into_iter().take(top).enumerate() {
+        println!("{}. secret: '{}', score: {:.2}", i + 1, secret, score);
+        println!("   '{}'", plain_text);
+    }
 }
 
 fn main() -> Result<(), String> {
     let cli = Cli::parse();
 
     match cli.command {
-        Command::Encrypt { command } => match command {
-            Encrypt::CaesarCipher { data, secret } => {
-                let encrypted = symmetric::caesar_cipher::encrypt(&data, secret);
-                println!("Encrypted message: '{}'", encrypted);
+        Command::Encrypt { command } => {
+            let encrypted = match command {
+                Encrypt::CaesarCipher {
+                    data,
+                    secret,
+                    preserve_case,
+                } => {
+                    let cipher =
+                        CaesarCipher::new(alphabet(preserve_case), UnknownSymbol::PassThrough);
+                    encrypt(&cipher, &secret, &data, Encoding::Text)?
+                }
+                Encrypt::VigenereCipher {
+                    data,
+                    secret,
+                    preserve_case,
+                    mode,
+                } => {
+                    let cipher =
+                        VigenereCipher::new(alphabet(preserve_case), UnknownSymbol::PassThrough)
+                            .with_mode(mode);
+                    encrypt(&cipher, &secret, &data, Encoding::Text)?
+                }
+                Encrypt::PlayfairCipher {
+                    data,
+                    secret,
+                    filler,
+                } => encrypt(&PlayfairCipher::new(filler), &secret, &data, Encoding::Text)?,
+                Encrypt::HillCipher { data, secret } => {
+                    encrypt(&HillCipher::default(), &secret, &data, Encoding::Text)?
+                }
+                Encrypt::AffineCipher {
+                    data,
+                    a,
+                    b,
+                    preserve_case,
+                } => {
+                    let cipher =
+                        AffineCipher::new(alphabet(preserve_case), UnknownSymbol::PassThrough);
+                    encrypt(&cipher, &(a, b), &data, Encoding::Text)?
+                }
+                Encrypt::SubstitutionCipher {
+                    data,
+                    secret,
+                    preserve_case,
+                } => {
+                    let cipher = SubstitutionCipher::new(
+                        alphabet(preserve_case),
+                        UnknownSymbol::PassThrough,
+                    );
+                    encrypt(&cipher, &secret, &data, Encoding::Text)?
+                }
+                Encrypt::Enigma {
+                    data,
+                    rotors,
+                    reflector,
+                    rings,
+                    positions,
+                    plugboard,
+                } => {
+                    let settings =
+                        Settings::new(&rotors, &reflector, &rings, &positions, &plugboard)?;
+                    encrypt(&Enigma, &settings, &data, Encoding::Text)?
+                }
+                Encrypt::Adfgvx {
+                    data,
+                    square,
+                    secret,
+                } => encrypt(&Adfgvx, &(square, secret), &data, Encoding::Text)?,
+                Encrypt::Bifid {
+                    data,
+                    secret,
+                    period,
+                } => encrypt(&Bifid::new(period), &secret, &data, Encoding::Text)?,
+                Encrypt::Trifid {
+                    data,
+                    secret,
+                    period,
+                } => encrypt(&Trifid::new(period), &secret, &data, Encoding::Text)?,
+                Encrypt::RailFence { data, rails } => {
+                    encrypt(&RailFence, &rails, &data, Encoding::Text)?
+                }
+                Encrypt::ColumnarTransposition {
+                    data,
+                    secret,
+                    second_secret,
+                } => {
+                    let secrets = std::iter::once(secret).chain(second_secret).collect();
+                    encrypt(&ColumnarTransposition, &secrets, &data, Encoding::Text)?
+                }
+                Encrypt::Aes {
+                    data,
+                    secret,
+                    trace,
+                } => {
+                    if let Some(format) = trace {
+                        let traces = advanced_encryption_standard::trace(
+                            data.as_bytes(),
+                            secret.as_bytes(),
+                            IV,
+                        )?;
+                        eprintln!("{}", trace::render(&traces, format));
+                    }
+                    encrypt(&Aes::new(*IV), &secret.into_bytes(), &data, Encoding::Hex)?
+                }
+                Encrypt::Des {
+                    data,
+                    secret,
+                    hex_secret,
+                    check_parity,
+                    trace,
+                } => {
+                    let parity = if check_parity {
+                        Parity::Check
+                    } else {
+                        Parity::Ignore
+                    };
+                    let secret = des_secret(secret, hex_secret, parity)?;
+                    if let Some(format) = trace {
+                        let traces = des::trace(data.as_bytes(), &secret, DES_IV, parity)?;
+                        eprintln!("{}", trace::render(&traces, format));
+                    }
+                    encrypt(
+                        &Des::new(*DES_IV).with_parity(parity),
+                        &secret,
+                        &data,
+                        Encoding::Hex,
+                    )?
+                }
+                Encrypt::TripleDes {
+                    data,
+                    secret,
+                    hex_secret,
+                } => encrypt(
+                    &TripleDes::new(*DES_IV),
+                    &des_secret(secret, hex_secret, Parity::Ignore)?,
+                    &data,
+                    Encoding::Hex,
+                )?,
+                Encrypt::OneTimePad { data, pad } => {
+                    let mut one_time_pad = read_pad(&pad)?;
+                    let (offset, secret) = one_time_pad.take(&data)?;
+                    let cipher = OneTimePad::new(
+                        one_time_pad.alphabet().clone(),
+                        UnknownSymbol::PassThrough,
+                    );
+                    let encrypted = encrypt(&cipher, &secret, &data, Encoding::Text)?;
+
+                    // the pad is saved before showing the message so the values can't be used again
+                    write_pad(&pad, &one_time_pad)?;
+                    println!("Pad offset: {}", offset);
+                    encrypted
+                }
+            };
+            println!("Encrypted message: '{}'", encrypted);
+        }
+        Command::Decrypt { command } => {
+            let decrypted = match command {
+                Decrypt::CaesarCipher {
+                    data,
+                    secret,
+                    preserve_case,
+                } => {
+                    let cipher =
+                        CaesarCipher::new(alphabet(preserve_case), UnknownSymbol::PassThrough);
+                    decrypt(&cipher, &secret, &data, Encoding::Text)?
+                }
+                Decrypt::VigenereCipher {
+                    data,
+                    secret,
+                    preserve_case,
+                    mode,
+                } => {
+                    let cipher =
+                        VigenereCipher::new(alphabet(preserve_case), UnknownSymbol::PassThrough)
+                            .with_mode(mode);
+                    decrypt(&cipher, &secret, &data, Encoding::Text)?
+                }
+                Decrypt::PlayfairCipher {
+                    data,
+                    secret,
+                    filler,
+                } => decrypt(&PlayfairCipher::new(filler), &secret, &data, Encoding::Text)?,
+                Decrypt::HillCipher { data, secret } => {
+                    decrypt(&HillCipher::default(), &secret, &data, Encoding::Text)?
+                }
+                Decrypt::AffineCipher {
+                    data,
+                    a,
+                    b,
+                    preserve_case,
+                } => {
+                    let cipher =
+                        AffineCipher::new(alphabet(preserve_case), UnknownSymbol::PassThrough);
+                    decrypt(&cipher, &(a, b), &data, Encoding::Text)?
+                }
+                Decrypt::SubstitutionCipher {
+                    data,
+                    secret,
+                    preserve_case,
+                } => {
+                    let cipher = SubstitutionCipher::new(
+                        alphabet(preserve_case),
+                        UnknownSymbol::PassThrough,
+                    );
+                    decrypt(&cipher, &secret, &data, Encoding::Text)?
+                }
+                Decrypt::Enigma {
+                    data,
+                    rotors,
+                    reflector,
+                    rings,
+                    positions,
+                    plugboard,
+                } => {
+                    let settings =
+                        Settings::new(&rotors, &reflector, &rings, &positions, &plugboard)?;
+                    decrypt(&Enigma, &settings, &data, Encoding::Text)?
+                }
+                Decrypt::Adfgvx {
+                    data,
+                    square,
+                    secret,
+                } => decrypt(&Adfgvx, &(square, secret), &data, Encoding::Text)?,
+                Decrypt::Bifid {
+                    data,
+                    secret,
+                    period,
+                } => decrypt(&Bifid::new(period), &secret, &data, Encoding::Text)?,
+                Decrypt::Trifid {
+                    data,
+                    secret,
+                    period,
+                } => decrypt(&Trifid::new(period), &secret, &data, Encoding::Text)?,
+                Decrypt::RailFence { data, rails } => {
+                    decrypt(&RailFence, &rails, &data, Encoding::Text)?
+                }
+                Decrypt::ColumnarTransposition {
+                    data,
+                    secret,
+                    second_secret,
+                } => {
+                    let secrets = std::iter::once(secret).chain(second_secret).collect();
+                    decrypt(&ColumnarTransposition, &secrets, &data, Encoding::Text)?
+                }
+                Decrypt::Aes {
+                    data,
+                    secret,
+                    trace,
+                } => {
+                    if let Some(format) = trace {
+                        let cipher_text =
+                            hex::decode(&data).map_err(|_| CipherError::InvalidEncoding)?;
+                        let traces = advanced_encryption_standard::trace_decryption(
+                            &cipher_text,
+                            secret.as_bytes(),
+                            IV,
+                        )?;
+                        eprintln!("{}", trace::render(&traces, format));
+                    }
+                    decrypt(&Aes::new(*IV), &secret.into_bytes(), &data, Encoding::Hex)?
+                }
+                Decrypt::Des {
+                    data,
+                    secret,
+                    hex_secret,
+                    check_parity,
+                    trace,
+                } => {
+                    let parity = if check_parity {
+                        Parity::Check
+                    } else {
+                        Parity::Ignore
+                    };
+                    let secret = des_secret(secret, hex_secret, parity)?;
+                    if let Some(format) = trace {
+                        let cipher_text =
+                            hex::decode(&data).map_err(|_| CipherError::InvalidEncoding)?;
+                        let traces = des::trace_decryption(&cipher_text, &secret, DES_IV, parity)?;
+                        eprintln!("{}", trace::render(&traces, format));
+                    }
+                    decrypt(
+                        &Des::new(*DES_IV).with_parity(parity),
+                        &secret,
+                        &data,
+                        Encoding::Hex,
+                    )?
+                }
+                Decrypt::TripleDes {
+                    data,
+                    secret,
+                    hex_secret,
+                } => decrypt(
+                    &TripleDes::new(*DES_IV),
+                    &des_secret(secret, hex_secret, Parity::Ignore)?,
+                    &data,
+                    Encoding::Hex,
+                )?,
+                Decrypt::OneTimePad { data, pad, offset } => {
+                    let one_time_pad = read_pad(&pad)?;
+                    let secret = one_time_pad.values_at(offset, &data)?;
+                    let cipher = OneTimePad::new(
+                        one_time_pad.alphabet().clone(),
+                        UnknownSymbol::PassThrough,
+                    );
+                    decrypt(&cipher, &secret, &data, Encoding::Text)?
+                }
+            };
+            println!("Decrypted message: '{}'", decrypted);
+        }
+        Command::Crack { command } => match command {
+            Crack::CaesarCipher {
+                input,
+                frequency_analysis,
+            } => {
+                let cipher_text = input.cipher_text()?;
+                let candidates = if frequency_analysis {
+                    caesar_cipher::crack_by_frequency(&cipher_text)
+                } else {
+                    caesar_cipher::crack(&cipher_text)
+                };
+                print_candidates(candidates, input.top);
             }
-            Encrypt::VigenereCipher { data, secret } => {
-                let encrypted = symmetric::vigenere_cipher::encrypt(&data, &secret);
-                println!("Encrypted message: '{}'", encrypted);
+            Crack::VigenereCipher { input, autokey } => {
+                let cipher_text = input.cipher_text()?;
+                let candidates = if autokey {
+                    vigenere_cipher::crack_autokey(&cipher_text)
+                } else {
+                    vigenere_cipher::crack(&cipher_text)
+                };
+                print_candidates(candidates, input.top);
             }
-            Encrypt::Aes { data, secret } => {
-                let encrypted = symmetric::advanced_encryption_standard::encrypt(
-                    data.as_bytes(),
-                    secret.as_bytes(),
-                    IV,
-                )?;
-                println!("Encrypted message: '{}'", encrypted);
+            Crack::AffineCipher { input } => {
+                let candidates = affine_cipher::crack(&input.cipher_text()?)
+                    .into_iter()
+                    .map(|((a, b), score, plain_text)| (format!("{} {}", a, b), score, plain_text))
+                    .collect();
+                print_candidates(candidates, input.top);
             }
-        },
-        Command::Decrypt { command } => match command {
-            Decrypt::CaesarCipher { data, secret } => {
-                let decrypted = symmetric::caesar_cipher::decrypt(&data, secret);
-                println!("Decrypted message: '{}'", decrypted);
+            Crack::SubstitutionCipher { input } => {
+                print_candidates(substitution_cipher::crack(&input.cipher_text()?), input.top)
             }
-            Decrypt::VigenereCipher { data, secret } => {
-                let decrypted = symmetric::vigenere_cipher::decrypt(&data, &secret);
-                println!("Decrypted message: '{}'", decrypted);
+            Crack::RailFence { input } => print_candidates(
+                transposition_cipher::crack_rail_fence(&input.cipher_text()?),
+                input.top,
+            ),
+            Crack::ColumnarTransposition { input } => print_candidates(
+                transposition_cipher::crack_columnar(&input.cipher_text()?),
+                input.top,
+            ),
+            Crack::TwoTimePad {
+                input,
+                second,
+                second_file,
+                crib,
+            } => {
+                let first = input.cipher_text()?;
+                let second = CrackInput {
+                    data: second,
+                    file: second_file,
+                    top: input.top,
+                }
+                .cipher_text()?;
+                if let Some(crib) = crib {
+                    let difference = one_time_pad::difference(&first, &second);
+                    for placement in one_time_pad::crib_drag(&difference, &crib)
+                        .into_iter()
+                        .take(input.top)
+                    {
+                        println!(
+                            "position {}, crib in the {} message, score: {:.2}",
+                            placement.position,
+                            if placement.crib_in_first {
+                                "first"
+                            } else {
+                                "second"
+                            },
+                            placement.score
+                        );
+                        println!("   other message: '{}'", placement.fragment);
+                    }
+                } else {
+                    let candidates = one_time_pad::recover_plain_texts(&first, &second);
+                    if candidates.is_empty() {
+                        println!("No candidates found");
+                    }
+                    for (i, (first, second)) in candidates.into_iter().take(input.top).enumerate() {
+                        println!("{}. '{}'", i + 1, first);
+                        println!("   '{}'", second);
+                    }
+                }
             }
-            Decrypt::Aes { data, secret } => {
-                let decrypted =
-                    symmetric::advanced_encryption_standard::decrypt(&data, secret.as_bytes(), IV)?;
-                println!("Decrypted message: '{}'", decrypted);
+            Crack::Enigma {
+                input,
+                crib,
+                offset,
+                rotors,
+                reflector,
+            } => {
+                let rotors = enigma::parse_rotor_list(&rotors)?;
+                print_candidates(
+                    enigma::crack(&input.cipher_text()?, &crib, offset, &rotors, reflector),
+                    input.top,
+                );
+            }
+            Crack::HillCipher {
+                plain_text,
+                cipher_text,
+                size,
+            } => {
+                let secret = hill_cipher::known_plain_text_attack(&plain_text, &cipher_text, size)?;
+                println!("secret: '{}'", secret);
             }
         },
+        Command::Identify { data } => {
+            let hypotheses = identify(&data);
+            if hypotheses.is_empty() {
+                println!("Not enough symbols to identify the cipher");
+            }
+            for hypothesis in hypotheses {
+                println!(
+                    "{:>5.1}%  {} ({})",
+                    hypothesis.confidence * 100.0,
+                    hypothesis.family,
+                    hypothesis.evidence
+                );
+            }
+        }
+        Command::MeetInTheMiddle {
+            key_bits,
+            table_bits,
+        } => {
+            let table_bits = table_bits.unwrap_or(key_bits);
+            meet_in_the_middle::check_bits(key_bits, table_bits)?;
+            let mut rng = rand::thread_rng();
+            let [k1, k2] = [(); 2].map(|_| {
+                meet_in_the_middle::reduced_key(rng.gen_range(0..1 << key_bits), key_bits)
+            });
+            let [known, check] = [b"Attack a", b"t dawn!!"].map(|plain_text| {
+                let plain_text = u64::from_be_bytes(*plain_text);
+                (
+                    plain_text,
+                    meet_in_the_middle::double_encrypt(plain_text, k1, k2),
+                )
+            });
+
+            let report = meet_in_the_middle::attack(known, &[check], key_bits, table_bits)?;
+            println!("Secret keys: {:016x} {:016x}", k1, k2);
+            for (k1, k2) in &report.keys {
+                println!("Found keys:  {:016x} {:016x}", k1, k2);
+            }
+            println!(
+                "{} DES operations in {:.2?}, brute force needs {}",
+                report.operations,
+                report.elapsed,
+                report.brute_force_operations()
+            );
+            println!(
+                "Table with {} entries, {} bytes",
+                report.table_entries, report.table_bytes
+            );
+            println!("\nTable bits  DES operations  Table entries");
+            for tradeoff in meet_in_the_middle::tradeoffs(key_bits) {
+                println!(
+                    "{:>10}  {:>14}  {:>13}",
+                    tradeoff.table_bits, tradeoff.operations, tradeoff.table_entries
+                );
+            }
+        }
+        Command::GeneratePad {
+            path,
+            length,
+            alphabet,
+        } => {
+            let pad = Pad::generate(length, alphabet);
+            let mut file = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            file.write_all(pad.to_string().as_bytes())
+                .map_err(|e| e.to_string())?;
+            println!("Pad with {} values saved to '{}'", length, path.display());
+        }
     }
 
     Ok(())
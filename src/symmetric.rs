@@ -4,7 +4,17 @@ const ALPHABET: [char; 27] = [
 ];
 
 pub mod advanced_encryption_standard;
+pub mod affine_cipher;
+pub mod alphabet;
 pub mod caesar_cipher;
-mod data_encryption_standard;
+pub mod data_encryption_standard;
+pub mod enigma;
+pub mod fractionating_cipher;
+pub mod hill_cipher;
 pub mod one_time_pad;
+pub mod playfair_cipher;
+pub mod substitution_cipher;
+pub mod trace;
+pub mod transposition_cipher;
+pub mod triple_data_encryption_standard;
 pub mod vigenere_cipher;
@@ -65,42 +65,81 @@
         The rounds will be run in reverse order.
 */
 
-use crypto::symmetriccipher::SymmetricCipherError;
-
-#[derive(Debug)]
-pub enum AesError {
-    InvalidSecretLength,
-    InvalidPadding,
-    InvalidHexEncryptedData,
-    InvalidDecodedValue,
-}
-
-impl From<SymmetricCipherError> for AesError {
+use crate::cipher::{BlockCipher, Cipher, KeyGen};
+use crate::error::CipherError;
+use crate::symmetric::trace::Trace;
+use crypto::aessafe::{AesSafe256Decryptor, AesSafe256Encryptor};
+use crypto::buffer::{BufferResult, ReadBuffer, WriteBuffer};
+use crypto::symmetriccipher::{BlockDecryptor, BlockEncryptor, SymmetricCipherError};
+use crypto::{aes, blockmodes, buffer};
+use rand::Rng;
+use std::array;
+
+const KEY_LENGTH: usize = 32;
+const BLOCK_LENGTH: usize = 16;
+const ROUNDS: usize = 14;
+
+// Multiplicative inverse in GF(2^8) followed by an affine transformation, indexed by the byte.
+const S_BOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const ROUND_CONSTANTS: [u8; 7] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40];
+
+impl From<SymmetricCipherError> for CipherError {
     fn from(value: SymmetricCipherError) -> Self {
         match value {
-            SymmetricCipherError::InvalidLength => Self::InvalidSecretLength,
+            // rust-crypto only reports an invalid length when the input is not a multiple of the block size.
+            SymmetricCipherError::InvalidLength => Self::InvalidEncoding,
             SymmetricCipherError::InvalidPadding => Self::InvalidPadding,
         }
     }
 }
 
-impl From<AesError> for String {
-    fn from(value: AesError) -> Self {
-        format!("{:?}", value)
+fn check_parameters(key: &[u8], iv: &[u8]) -> Result<(), CipherError> {
+    if key.len() != KEY_LENGTH {
+        return Err(CipherError::InvalidKeyLength {
+            expected: KEY_LENGTH,
+            actual: key.len(),
+        });
     }
-}
 
-use crypto::buffer::{BufferResult, ReadBuffer, WriteBuffer};
-use crypto::{aes, blockmodes, buffer};
+    if iv.len() != BLOCK_LENGTH {
+        return Err(CipherError::InvalidIvLength {
+            expected: BLOCK_LENGTH,
+            actual: iv.len(),
+        });
+    }
+
+    Ok(())
+}
 
 // Example from https://github.com/DaGenix/rust-crypto/blob/master/examples/symmetriccipher.rs
 
 // Encrypt a buffer with the given key and iv using
-// AES-256/CBC/Pkcs encryption.
-pub fn encrypt(data: &[u8], key: &[u8], iv: &[u8]) -> Result<String, AesError> {
-    if key.len() != 32 {
-        return Err(AesError::InvalidSecretLength);
-    };
+// AES-256/CBC/Pkcs encryption, the result is hex encoded.
+#[allow(dead_code)]
+pub fn encrypt(data: &[u8], key: &[u8], iv: &[u8]) -> Result<String, CipherError> {
+    encrypt_bytes(data, key, iv).map(hex::encode)
+}
+
+fn encrypt_bytes(data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, CipherError> {
+    check_parameters(key, iv)?;
     // Create an encryptor instance of the best performing
     // type available for the platform.
     let mut encryptor =
@@ -148,7 +187,7 @@ pub fn encrypt(data: &[u8], key: &[u8], iv: &[u8]) -> Result<String, AesError> {
                 .take_read_buffer()
                 .take_remaining()
                 .iter()
-                .map(|&i| i),
+                .copied(),
         );
 
         match result {
@@ -157,7 +196,7 @@ pub fn encrypt(data: &[u8], key: &[u8], iv: &[u8]) -> Result<String, AesError> {
         }
     }
 
-    Ok(hex::encode(final_result))
+    Ok(final_result)
 }
 
 // Decrypts a buffer with the given key and iv using
@@ -167,15 +206,24 @@ pub fn encrypt(data: &[u8], key: &[u8], iv: &[u8]) -> Result<String, AesError> {
 // comments in that function. In non-example code, if desired, it is possible to
 // share much of the implementation using closures to hide the operation
 // being performed. However, such code would make this example less clear.
-pub fn decrypt(encrypted_data: &str, key: &[u8], iv: &[u8]) -> Result<String, AesError> {
+#[allow(dead_code)]
+pub fn decrypt(encrypted_data: &str, key: &[u8], iv: &[u8]) -> Result<String, CipherError> {
+    check_parameters(key, iv)?;
     let Some(encrypted_data) = hex::decode(encrypted_data).ok() else {
-        return Err(AesError::InvalidHexEncryptedData)
+        return Err(CipherError::InvalidEncoding);
     };
+    let decrypted_data = decrypt_bytes(&encrypted_data, key, iv)?;
+
+    String::from_utf8(decrypted_data).map_err(|_| CipherError::InvalidEncoding)
+}
+
+fn decrypt_bytes(encrypted_data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, CipherError> {
+    check_parameters(key, iv)?;
     let mut decryptor =
         aes::cbc_decryptor(aes::KeySize::KeySize256, key, iv, blockmodes::PkcsPadding);
 
     let mut final_result = Vec::<u8>::new();
-    let mut read_buffer = buffer::RefReadBuffer::new(&encrypted_data);
+    let mut read_buffer = buffer::RefReadBuffer::new(encrypted_data);
     let mut buffer = [0; 4096];
     let mut write_buffer = buffer::RefWriteBuffer::new(&mut buffer);
 
@@ -186,7 +234,7 @@ pub fn decrypt(encrypted_data: &str, key: &[u8], iv: &[u8]) -> Result<String, Ae
                 .take_read_buffer()
                 .take_remaining()
                 .iter()
-                .map(|&i| i),
+                .copied(),
         );
         match result {
             BufferResult::BufferUnderflow => break,
@@ -194,7 +242,271 @@ pub fn decrypt(encrypted_data: &str, key: &[u8], iv: &[u8]) -> Result<String, Ae
         }
     }
 
-    Ok(String::from_utf8(final_result).map_err(|_| AesError::InvalidDecodedValue)?)
+    Ok(final_result)
+}
+
+/*
+    Round by round
+    rust-crypto does the encryption above, the functions below follow the tutorial at the top of the file step by step
+    so every intermediate state can be traced, the state is stored column by column like the input bytes:
+
+    bytes  0  1  2  3  4  5 ...      state  0  4  8 12
+                                            1  5  9 13
+                                            2  6 10 14
+                                            3  7 11 15
+*/
+
+// The 15 round keys, built one word (a column of 4 bytes) at a time from the 8 words of the key.
+fn round_keys(key: &[u8]) -> [[u8; BLOCK_LENGTH]; ROUNDS + 1] {
+    let key_words = KEY_LENGTH / 4;
+    let mut words = key
+        .chunks(4)
+        .map(|word| [word[0], word[1], word[2], word[3]])
+        .collect::<Vec<[u8; 4]>>();
+
+    for i in key_words..4 * (ROUNDS + 1) {
+        let mut word = words[i - 1];
+        if i % key_words == 0 {
+            word.rotate_left(1);
+            word = word.map(|b| S_BOX[b as usize]);
+            word[0] ^= ROUND_CONSTANTS[i / key_words - 1];
+        } else if i % key_words == 4 {
+            // only 256 bit keys substitute the word in the middle of the key
+            word = word.map(|b| S_BOX[b as usize]);
+        }
+        let previous = words[i - key_words];
+        words.push(array::from_fn(|j| word[j] ^ previous[j]));
+    }
+
+    array::from_fn(|round| array::from_fn(|j| words[round * 4 + j / 4][j % 4]))
+}
+
+fn add_round_key(state: &mut [u8; BLOCK_LENGTH], round_key: &[u8; BLOCK_LENGTH]) {
+    state.iter_mut().zip(round_key).for_each(|(b, k)| *b ^= k);
+}
+
+fn sub_bytes(state: &mut [u8; BLOCK_LENGTH]) {
+    state.iter_mut().for_each(|b| *b = S_BOX[*b as usize]);
+}
+
+// Row r is shifted r positions to the left.
+fn shift_rows(state: &mut [u8; BLOCK_LENGTH]) {
+    let old = *state;
+    for (i, b) in state.iter_mut().enumerate() {
+        let (column, row) = (i / 4, i % 4);
+        *b = old[((column + row) % 4) * 4 + row];
+    }
+}
+
+// Multiplication by 2 in GF(2^8).
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ if b & 0x80 != 0 { 0x1B } else { 0 }
+}
+
+// Every column is multiplied by the matrix [2 3 1 1] rotated on each row.
+fn mix_columns(state: &mut [u8; BLOCK_LENGTH]) {
+    for column in state.chunks_mut(4) {
+        let a = [column[0], column[1], column[2], column[3]];
+        let all = a[0] ^ a[1] ^ a[2] ^ a[3];
+        for (i, b) in column.iter_mut().enumerate() {
+            *b = a[i] ^ all ^ xtime(a[i] ^ a[(i + 1) % 4]);
+        }
+    }
+}
+
+// The S-box is a permutation, so each byte goes back to its position in it.
+fn inv_sub_bytes(state: &mut [u8; BLOCK_LENGTH]) {
+    state.iter_mut().for_each(|b| {
+        *b = S_BOX
+            .iter()
+            .position(|s| s == b)
+            .expect("the S-box has every byte") as u8
+    });
+}
+
+// Row r is shifted r positions to the right.
+fn inv_shift_rows(state: &mut [u8; BLOCK_LENGTH]) {
+    let old = *state;
+    for (i, b) in state.iter_mut().enumerate() {
+        let (column, row) = (i / 4, i % 4);
+        *b = old[((column + 4 - row) % 4) * 4 + row];
+    }
+}
+
+// The inverse matrix [14 11 13 9] is [5 0 4 0] times [2 3 1 1], so a cheap step is followed by mix_columns.
+fn inv_mix_columns(state: &mut [u8; BLOCK_LENGTH]) {
+    for column in state.chunks_mut(4) {
+        let u = xtime(xtime(column[0] ^ column[2]));
+        let v = xtime(xtime(column[1] ^ column[3]));
+        column[0] ^= u;
+        column[1] ^= v;
+        column[2] ^= u;
+        column[3] ^= v;
+    }
+    mix_columns(state);
+}
+
+// Encrypts one block recording every step with the names used in FIPS-197 appendix C.
+pub fn trace_block(
+    key: &[u8],
+    block: &[u8],
+    trace: &mut Trace,
+) -> Result<[u8; BLOCK_LENGTH], CipherError> {
+    check_parameters(key, &[0; BLOCK_LENGTH])?;
+    if block.len() != BLOCK_LENGTH {
+        return Err(CipherError::InvalidBlock);
+    }
+
+    let round_keys = round_keys(key);
+    let mut state: [u8; BLOCK_LENGTH] = array::from_fn(|i| block[i]);
+    trace.record(0, "input", &state);
+    trace.record(0, "k_sch", &round_keys[0]);
+    add_round_key(&mut state, &round_keys[0]);
+
+    for (round, round_key) in round_keys.iter().enumerate().skip(1) {
+        trace.record(round, "start", &state);
+        sub_bytes(&mut state);
+        trace.record(round, "s_box", &state);
+        shift_rows(&mut state);
+        trace.record(round, "s_row", &state);
+        if round != ROUNDS {
+            mix_columns(&mut state);
+            trace.record(round, "m_col", &state);
+        }
+        trace.record(round, "k_sch", round_key);
+        add_round_key(&mut state, round_key);
+    }
+
+    trace.record(ROUNDS, "output", &state);
+    Ok(state)
+}
+
+// Decrypts one block with the inverse cipher of FIPS-197, the round keys from the last to the first,
+// recording every step with the names of appendix C (an "i" in front of each one).
+pub fn trace_inverse_block(
+    key: &[u8],
+    block: &[u8],
+    trace: &mut Trace,
+) -> Result<[u8; BLOCK_LENGTH], CipherError> {
+    check_parameters(key, &[0; BLOCK_LENGTH])?;
+    if block.len() != BLOCK_LENGTH {
+        return Err(CipherError::InvalidBlock);
+    }
+
+    let round_keys = round_keys(key);
+    let mut state: [u8; BLOCK_LENGTH] = array::from_fn(|i| block[i]);
+    trace.record(0, "iinput", &state);
+    trace.record(0, "ik_sch", &round_keys[ROUNDS]);
+    add_round_key(&mut state, &round_keys[ROUNDS]);
+
+    for round in 1..=ROUNDS {
+        let round_key = &round_keys[ROUNDS - round];
+        trace.record(round, "istart", &state);
+        inv_shift_rows(&mut state);
+        trace.record(round, "is_row", &state);
+        inv_sub_bytes(&mut state);
+        trace.record(round, "is_box", &state);
+        trace.record(round, "ik_sch", round_key);
+        add_round_key(&mut state, round_key);
+        if round != ROUNDS {
+            trace.record(round, "ik_add", &state);
+            inv_mix_columns(&mut state);
+        }
+    }
+
+    trace.record(ROUNDS, "ioutput", &state);
+    Ok(state)
+}
+
+// One trace per block of the AES-256/CBC/Pkcs encryption.
+pub fn trace(data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<Trace>, CipherError> {
+    check_parameters(key, iv)?;
+    let padding = BLOCK_LENGTH - data.len() % BLOCK_LENGTH;
+    let mut data = data.to_vec();
+    data.extend(std::iter::repeat_n(padding as u8, padding));
+
+    let mut previous: [u8; BLOCK_LENGTH] = array::from_fn(|i| iv[i]);
+    let mut traces = vec![];
+    for (i, block) in data.chunks(BLOCK_LENGTH).enumerate() {
+        let block: [u8; BLOCK_LENGTH] = array::from_fn(|j| block[j] ^ previous[j]);
+        let mut trace = Trace::new("AES", i);
+        previous = trace_block(key, &block, &mut trace)?;
+        traces.push(trace);
+    }
+    Ok(traces)
+}
+
+// One trace per block of the decryption, the output of each trace is xored with the previous cipher block afterwards.
+pub fn trace_decryption(data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<Trace>, CipherError> {
+    check_parameters(key, iv)?;
+    if data.is_empty() || !data.len().is_multiple_of(BLOCK_LENGTH) {
+        return Err(CipherError::InvalidEncoding);
+    }
+
+    let mut traces = vec![];
+    for (i, block) in data.chunks(BLOCK_LENGTH).enumerate() {
+        let mut trace = Trace::new("AES", i);
+        trace_inverse_block(key, block, &mut trace)?;
+        traces.push(trace);
+    }
+    Ok(traces)
+}
+
+pub struct Aes {
+    iv: [u8; BLOCK_LENGTH],
+}
+
+impl Aes {
+    pub fn new(iv: [u8; BLOCK_LENGTH]) -> Self {
+        Self { iv }
+    }
+}
+
+impl Cipher for Aes {
+    type Key = Vec<u8>;
+
+    fn encrypt(&self, key: &Vec<u8>, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        encrypt_bytes(plain_text, key, &self.iv)
+    }
+
+    fn decrypt(&self, key: &Vec<u8>, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        decrypt_bytes(cipher_text, key, &self.iv)
+    }
+}
+
+// A single block without chaining or padding, the building block of the CBC mode used above.
+impl BlockCipher for Aes {
+    const BLOCK_SIZE: usize = BLOCK_LENGTH;
+
+    fn encrypt_block(&self, key: &Vec<u8>, block: &[u8]) -> Result<Vec<u8>, CipherError> {
+        check_parameters(key, &self.iv)?;
+        if block.len() != BLOCK_LENGTH {
+            return Err(CipherError::InvalidBlock);
+        }
+
+        let mut output = vec![0; BLOCK_LENGTH];
+        AesSafe256Encryptor::new(key).encrypt_block(block, &mut output);
+        Ok(output)
+    }
+
+    fn decrypt_block(&self, key: &Vec<u8>, block: &[u8]) -> Result<Vec<u8>, CipherError> {
+        check_parameters(key, &self.iv)?;
+        if block.len() != BLOCK_LENGTH {
+            return Err(CipherError::InvalidBlock);
+        }
+
+        let mut output = vec![0; BLOCK_LENGTH];
+        AesSafe256Decryptor::new(key).decrypt_block(block, &mut output);
+        Ok(output)
+    }
+}
+
+impl KeyGen for Aes {
+    fn generate_key(&self) -> Vec<u8> {
+        let mut key = vec![0; KEY_LENGTH];
+        rand::thread_rng().fill(&mut key[..]);
+        key
+    }
 }
 
 #[cfg(test)]
@@ -213,4 +525,175 @@ mod tests {
         let decrypted_data = decrypt(&hex, key, iv).unwrap();
         assert_eq!(message, decrypted_data);
     }
+
+    #[test]
+    fn encrypt_decrypt_through_traits() {
+        let cipher = Aes::new(*b"0000000000000000");
+        let key = b"mysecretpasswordmysecretpassword".to_vec();
+
+        let encrypted = Cipher::encrypt(&cipher, &key, b"Hello World").unwrap();
+        assert_eq!(hex::encode(&encrypted), "9ed5a2c1484805d74255b86d8ef652b3");
+        assert_eq!(
+            Cipher::decrypt(&cipher, &key, &encrypted).unwrap(),
+            b"Hello World"
+        );
+
+        // FIPS-197 appendix C.3
+        let key = (0..32).collect::<Vec<u8>>();
+        let block = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let encrypted = cipher.encrypt_block(&key, &block).unwrap();
+        assert_eq!(hex::encode(&encrypted), "8ea2b7ca516745bfeafc49904b496089");
+        assert_eq!(cipher.decrypt_block(&key, &encrypted).unwrap(), block);
+        assert_eq!(
+            cipher.encrypt_block(&key, &block[1..]),
+            Err(CipherError::InvalidBlock)
+        );
+
+        let key = cipher.generate_key();
+        let encrypted = Cipher::encrypt(&cipher, &key, b"Hello World").unwrap();
+        assert_eq!(
+            Cipher::decrypt(&cipher, &key, &encrypted).unwrap(),
+            b"Hello World"
+        );
+    }
+
+    #[test]
+    fn trace_rounds() {
+        // FIPS-197 appendix C.3
+        let key = (0..32).collect::<Vec<u8>>();
+        let block = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let mut block_trace = Trace::new("AES", 0);
+        let output = trace_block(&key, &block, &mut block_trace).unwrap();
+        assert_eq!(hex::encode(output), "8ea2b7ca516745bfeafc49904b496089");
+        assert_eq!(
+            block_trace.value(1, "start"),
+            Some("00102030405060708090a0b0c0d0e0f0")
+        );
+        assert_eq!(
+            block_trace.value(1, "s_box"),
+            Some("63cab7040953d051cd60e0e7ba70e18c")
+        );
+        assert_eq!(
+            block_trace.value(1, "s_row"),
+            Some("6353e08c0960e104cd70b751bacad0e7")
+        );
+        assert_eq!(
+            block_trace.value(1, "m_col"),
+            Some("5f72641557f5bc92f7be3b291db9f91a")
+        );
+        assert_eq!(
+            block_trace.value(1, "k_sch"),
+            Some("101112131415161718191a1b1c1d1e1f")
+        );
+        assert_eq!(
+            block_trace.value(2, "start"),
+            Some("4f63760643e0aa85efa7213201a4e705")
+        );
+        assert_eq!(
+            block_trace.value(2, "k_sch"),
+            Some("a573c29fa176c498a97fce93a572c09c")
+        );
+        assert_eq!(block_trace.value(ROUNDS, "m_col"), None);
+
+        // FIPS-197 appendix C.3, inverse cipher
+        let mut inverse_trace = Trace::new("AES", 0);
+        let plain = trace_inverse_block(&key, &output, &mut inverse_trace).unwrap();
+        assert_eq!(plain[..], block);
+        assert_eq!(
+            inverse_trace.value(0, "ik_sch"),
+            Some("24fc79ccbf0979e9371ac23c6d68de36")
+        );
+        assert_eq!(
+            inverse_trace.value(1, "istart"),
+            Some("aa5ece06ee6e3c56dde68bac2621bebf")
+        );
+        assert_eq!(
+            inverse_trace.value(1, "is_row"),
+            Some("aa218b56ee5ebeacdd6ecebf26e63c06")
+        );
+        assert_eq!(
+            inverse_trace.value(1, "is_box"),
+            Some("627bceb9999d5aaac945ecf423f56da5")
+        );
+        // every inverse round undoes an encryption round, from the last one to the first
+        for round in 1..=ROUNDS {
+            assert_eq!(
+                inverse_trace.value(round, "is_box"),
+                block_trace.value(ROUNDS + 1 - round, "start")
+            );
+        }
+        assert_eq!(
+            inverse_trace.value(ROUNDS, "ioutput"),
+            Some("00112233445566778899aabbccddeeff")
+        );
+
+        // the traced rounds give the same blocks as rust-crypto
+        let cipher = Aes::new(*b"0000000000000000");
+        for _ in 0..10 {
+            let key = cipher.generate_key();
+            let block = rand::thread_rng().gen::<[u8; BLOCK_LENGTH]>();
+            assert_eq!(
+                trace_block(&key, &block, &mut Trace::new("AES", 0)).unwrap()[..],
+                cipher.encrypt_block(&key, &block).unwrap()
+            );
+            assert_eq!(
+                trace_inverse_block(&key, &block, &mut Trace::new("AES", 0)).unwrap()[..],
+                cipher.decrypt_block(&key, &block).unwrap()
+            );
+        }
+
+        let traces = trace(
+            b"Hello World",
+            b"mysecretpasswordmysecretpassword",
+            b"0000000000000000",
+        )
+        .unwrap();
+        assert_eq!(traces.len(), 1);
+        assert_eq!(
+            traces[0].value(ROUNDS, "output"),
+            Some("9ed5a2c1484805d74255b86d8ef652b3")
+        );
+        let traces = trace_decryption(
+            &hex::decode("9ed5a2c1484805d74255b86d8ef652b3").unwrap(),
+            b"mysecretpasswordmysecretpassword",
+            b"0000000000000000",
+        )
+        .unwrap();
+        assert_eq!(traces.len(), 1);
+        assert_eq!(
+            traces[0].value(0, "iinput"),
+            Some("9ed5a2c1484805d74255b86d8ef652b3")
+        );
+        assert!(trace_decryption(&[0; 5], &[0; KEY_LENGTH], &[0; BLOCK_LENGTH]).is_err());
+    }
+
+    #[test]
+    fn reject_invalid_input() {
+        let key = b"mysecretpasswordmysecretpassword";
+        let iv = b"0000000000000000";
+
+        assert_eq!(
+            encrypt(b"Hello World", b"short", iv),
+            Err(CipherError::InvalidKeyLength {
+                expected: 32,
+                actual: 5
+            })
+        );
+        assert_eq!(
+            decrypt("not hex", key, iv),
+            Err(CipherError::InvalidEncoding)
+        );
+        assert_eq!(
+            decrypt("9ed5a2c1484805d74255b86d8ef652", key, iv),
+            Err(CipherError::InvalidEncoding)
+        );
+        assert_eq!(
+            decrypt(
+                "9ed5a2c1484805d74255b86d8ef652b3",
+                b"mysecretpasswordmysecretpassworx",
+                iv
+            ),
+            Err(CipherError::InvalidPadding)
+        );
+    }
 }
@@ -0,0 +1,257 @@
+use crate::analysis::fitness::{self, Fitness, Quadgrams};
+use crate::asymmetric::rsa::{euclidean_gcd, modular_inverse};
+use crate::cipher::{to_text, Cipher, KeyGen};
+use crate::error::CipherError;
+use crate::symmetric::alphabet::{Alphabet, UnknownSymbol};
+use rand::Rng;
+
+/*
+    Affine cipher multiplies the position of each symbol by `a` and then shifts it by `b`,
+    the secret is the pair (a, b) and M is the length of the alphabet (27 by default).
+
+    encryption
+    E(x) = (a * x + b) mod M
+    decryption
+    D(y) = a.pow(-1) * (y - b) mod M
+
+    a.pow(-1) is the modular inverse of `a`, which only exists when gcd(a, M) == 1,
+    otherwise two symbols would be encrypted to the same one and the text couldn't be decrypted.
+    With 27 symbols `a` can't be a multiple of 3, leaving 18 * 27 = 486 possible secrets.
+
+    Caesar cipher is the affine cipher with a == 1.
+*/
+
+// Secret reduced to the alphabet, `a` must have a modular inverse.
+fn check_secret((a, b): (i32, i32), alphabet: &Alphabet) -> Result<(usize, usize), CipherError> {
+    let length = alphabet.len() as i32;
+    let (a, b) = (a.rem_euclid(length) as usize, b.rem_euclid(length) as usize);
+    if euclidean_gcd(a as u128, alphabet.len() as u128) != 1 {
+        return Err(CipherError::InvalidKey(
+            "a must be coprime with the length of the alphabet",
+        ));
+    }
+
+    Ok((a, b))
+}
+
+fn inverse(a: usize, length: usize) -> usize {
+    modular_inverse(a as i128, length as i128)
+        .1
+        .rem_euclid(length as i128) as usize
+}
+
+#[allow(dead_code)]
+pub fn encrypt(plain_text: &str, secret: (i32, i32)) -> Result<String, CipherError> {
+    encrypt_with(
+        plain_text,
+        secret,
+        &Alphabet::default(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+pub fn decrypt(cipher_text: &str, secret: (i32, i32)) -> Result<String, CipherError> {
+    decrypt_with(
+        cipher_text,
+        secret,
+        &Alphabet::default(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+pub fn encrypt_with(
+    plain_text: &str,
+    secret: (i32, i32),
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    let (a, b) = check_secret(secret, alphabet)?;
+    alphabet.transform(plain_text, policy, |index| a * index + b)
+}
+
+pub fn decrypt_with(
+    cipher_text: &str,
+    secret: (i32, i32),
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    let (a, b) = check_secret(secret, alphabet)?;
+    let a_inverse = inverse(a, alphabet.len());
+    alphabet.transform(cipher_text, policy, |index| {
+        a_inverse * (alphabet.len() + index - b)
+    })
+}
+
+// Every (a, b) that can be used as a secret for an alphabet with `length` symbols.
+fn secrets(length: usize) -> impl Iterator<Item = (i32, i32)> {
+    (1..length)
+        .filter(move |a| euclidean_gcd(*a as u128, length as u128) == 1)
+        .flat_map(move |a| (0..length).map(move |b| (a as i32, b as i32)))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AffineCipher {
+    pub alphabet: Alphabet,
+    pub policy: UnknownSymbol,
+}
+
+impl AffineCipher {
+    pub fn new(alphabet: Alphabet, policy: UnknownSymbol) -> Self {
+        Self { alphabet, policy }
+    }
+}
+
+impl Cipher for AffineCipher {
+    type Key = (i32, i32);
+
+    fn encrypt(&self, key: &(i32, i32), plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        encrypt_with(to_text(plain_text)?, *key, &self.alphabet, self.policy)
+            .map(String::into_bytes)
+    }
+
+    fn decrypt(&self, key: &(i32, i32), cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        decrypt_with(to_text(cipher_text)?, *key, &self.alphabet, self.policy)
+            .map(String::into_bytes)
+    }
+}
+
+impl KeyGen for AffineCipher {
+    // (1, 0) would leave the text untouched.
+    fn generate_key(&self) -> (i32, i32) {
+        let secrets = secrets(self.alphabet.len())
+            .filter(|secret| *secret != (1, 0))
+            .collect::<Vec<(i32, i32)>>();
+        secrets[rand::thread_rng().gen_range(0..secrets.len())]
+    }
+}
+
+/*
+    Breaking affine cipher
+
+    Brute force attack: there are only 486 secrets, so every one of them is used to decrypt the message
+    and the candidates are ranked with english quadgrams.
+
+    Known plain text attack: knowing two plain text symbols (x1, x2) and their encrypted symbols (y1, y2)
+    gives two equations with two unknowns:
+
+    y1 = a * x1 + b mod M
+    y2 = a * x2 + b mod M
+
+    subtracting them, y1 - y2 = a * (x1 - x2) mod M, so a = (y1 - y2) * (x1 - x2).pow(-1) mod M
+    and then b = y1 - a * x1 mod M.
+    When x1 - x2 has no modular inverse (with 27 symbols, when it's a multiple of 3) there is more
+    than one possible secret and another pair of symbols must be used.
+*/
+
+#[allow(dead_code)]
+pub fn brute_force_attack(cipher_text: &str) -> Vec<((i32, i32), String)> {
+    secrets(Alphabet::default().len())
+        .filter_map(|secret| Some((secret, decrypt(cipher_text, secret).ok()?)))
+        .collect()
+}
+
+// Returns (secret, score, plain text) for every possible secret, the most likely one first.
+pub fn crack(cipher_text: &str) -> Vec<((i32, i32), f64, String)> {
+    crack_with(cipher_text, Quadgrams::english())
+}
+
+pub fn crack_with(cipher_text: &str, fitness: &dyn Fitness) -> Vec<((i32, i32), f64, String)> {
+    fitness::rank(brute_force_attack(cipher_text), fitness)
+}
+
+// Each pair is (plain text symbol, cipher text symbol).
+#[allow(dead_code)]
+pub fn known_plain_text_attack(
+    first: (char, char),
+    second: (char, char),
+) -> Result<(i32, i32), CipherError> {
+    let alphabet = Alphabet::default();
+    let length = alphabet.len();
+    let position = |c: char| {
+        alphabet
+            .position(c)
+            .ok_or(CipherError::InvalidInputSymbol(c))
+    };
+    let (x1, y1) = (position(first.0)?, position(first.1)?);
+    let (x2, y2) = (position(second.0)?, position(second.1)?);
+
+    let difference = (length + x1 - x2) % length;
+    if euclidean_gcd(difference as u128, length as u128) != 1 {
+        return Err(CipherError::InvalidKey(
+            "the plain text symbols don't identify a single secret",
+        ));
+    }
+
+    let a = (length + y1 - y2) % length * inverse(difference, length) % length;
+    let b = (length * length + y1 - a * x1) % length;
+    check_secret((a as i32, b as i32), &alphabet)
+        .map(|(a, b)| (a as i32, b as i32))
+        .map_err(|_| CipherError::InvalidKey("the symbols weren't encrypted with affine cipher"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_message() {
+        let result = encrypt("HELLO WORLD", (5, 8)).unwrap();
+        assert_eq!(result, "UFNNBHOBQNA");
+        assert_eq!(decrypt(&result, (5, 8)).unwrap(), "HELLO WORLD");
+
+        // a == 1 is a caesar cipher and negative values wrap around
+        assert_eq!(encrypt("HELLO WORLD", (1, 3)).unwrap(), "KHOORCZRUOG");
+        assert_eq!(encrypt("HELLO WORLD", (-22, -19)).unwrap(), "UFNNBHOBQNA");
+
+        for a in [0, 3, 9, 27] {
+            assert_eq!(
+                encrypt("HELLO", (a, 1)),
+                Err(CipherError::InvalidKey(
+                    "a must be coprime with the length of the alphabet"
+                ))
+            );
+        }
+
+        // 26 letters, so a == 13 is not valid anymore but a == 3 is
+        let cipher = AffineCipher::new(Alphabet::letters(), UnknownSymbol::PassThrough);
+        assert!(cipher.encrypt(&(13, 1), b"Hello").is_err());
+        let encrypted = cipher.encrypt(&(3, 7), b"Hello, World!").unwrap();
+        assert_eq!(
+            cipher.decrypt(&(3, 7), &encrypted).unwrap(),
+            b"Hello, World!"
+        );
+    }
+
+    #[test]
+    fn brute_force_and_crack() {
+        assert_eq!(brute_force_attack("ABC").len(), 486);
+
+        let message = "MEET ME AT THE USUAL PLACE AT TEN RATHER THAN EIGHT";
+        let cipher_text = encrypt(message, (20, 11)).unwrap();
+        let result = crack(&cipher_text);
+        assert_eq!(result[0].0, (20, 11));
+        assert_eq!(result[0].2, message);
+    }
+
+    #[test]
+    fn known_plain_text() {
+        let cipher_text = encrypt("HELLO WORLD", (7, 2)).unwrap();
+        let symbols = cipher_text.chars().collect::<Vec<char>>();
+        assert_eq!(
+            known_plain_text_attack(('H', symbols[0]), ('L', symbols[2])),
+            Ok((7, 2))
+        );
+
+        // H - E == 3 has no modular inverse
+        assert_eq!(
+            known_plain_text_attack(('H', symbols[0]), ('E', symbols[1])),
+            Err(CipherError::InvalidKey(
+                "the plain text symbols don't identify a single secret"
+            ))
+        );
+        assert_eq!(
+            known_plain_text_attack(('H', '!'), ('E', 'B')),
+            Err(CipherError::InvalidInputSymbol('!'))
+        );
+    }
+}
@@ -0,0 +1,245 @@
+/*
+    The alphabet is the list of symbols a classical cipher knows how to shift,
+    the position of each symbol in the list is the number used in the modular arithmetic of the cipher,
+    so the size of the alphabet is the modulus.
+
+    Built-in alphabets
+    -> Uppercase: space plus the 26 uppercase letters, lowercase input is folded into uppercase.
+    -> Letters: the 26 letters keeping the case of the input, so "Hello, World!" stays readable after decryption.
+    -> Printable ASCII: every visible ascii character plus space (from ' ' to '~').
+    -> Bytes: the 256 values of a byte, each one represented by the char with the same code point.
+    -> Custom: any string of distinct characters.
+
+    Alphabets are written by name ("uppercase", "letters", "printable-ascii", "bytes"),
+    any other string is read as a custom alphabet.
+
+    Characters outside the alphabet can't be shifted, so we need a policy for them:
+    pass them through untouched, reject the whole text or drop them from the output.
+*/
+
+use crate::error::CipherError;
+use crate::symmetric::ALPHABET;
+use std::fmt;
+use std::str::FromStr;
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownSymbol {
+    #[default]
+    PassThrough,
+    Reject,
+    Drop,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    symbols: Vec<char>,
+    ignore_case: bool,
+    preserve_case: bool,
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Self::uppercase()
+    }
+}
+
+#[allow(dead_code)]
+impl Alphabet {
+    pub fn new(symbols: &str) -> Result<Self, CipherError> {
+        let symbols = symbols.chars().collect::<Vec<char>>();
+        if symbols.is_empty() {
+            return Err(CipherError::EmptyAlphabet);
+        }
+
+        for (i, c) in symbols.iter().enumerate() {
+            if symbols[..i].contains(c) {
+                return Err(CipherError::DuplicatedSymbol(*c));
+            }
+        }
+
+        Ok(Self {
+            symbols,
+            ignore_case: false,
+            preserve_case: false,
+        })
+    }
+
+    pub fn uppercase() -> Self {
+        Self {
+            symbols: ALPHABET.to_vec(),
+            ignore_case: true,
+            preserve_case: false,
+        }
+    }
+
+    pub fn letters() -> Self {
+        Self {
+            symbols: ('A'..='Z').collect(),
+            ignore_case: true,
+            preserve_case: true,
+        }
+    }
+
+    pub fn printable_ascii() -> Self {
+        Self {
+            symbols: (' '..='~').collect(),
+            ignore_case: false,
+            preserve_case: false,
+        }
+    }
+
+    pub fn bytes() -> Self {
+        Self {
+            symbols: (0..=u8::MAX).map(char::from).collect(),
+            ignore_case: false,
+            preserve_case: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn position(&self, c: char) -> Option<usize> {
+        let c = if self.ignore_case {
+            c.to_ascii_uppercase()
+        } else {
+            c
+        };
+        self.symbols.iter().position(|a| *a == c)
+    }
+
+    // Number of symbols of the text that belong to the alphabet.
+    pub fn count(&self, text: &str) -> usize {
+        text.chars().filter(|c| self.position(*c).is_some()).count()
+    }
+
+    pub fn symbol(&self, index: usize) -> char {
+        self.symbols[index % self.symbols.len()]
+    }
+
+    // Replaces every symbol of the text by the symbol at the index returned by `shift`,
+    // `shift` is only called for symbols of the alphabet, unknown ones follow the given policy.
+    pub fn transform<F>(
+        &self,
+        text: &str,
+        policy: UnknownSymbol,
+        mut shift: F,
+    ) -> Result<String, CipherError>
+    where
+        F: FnMut(usize) -> usize,
+    {
+        let mut result = String::new();
+        for c in text.chars() {
+            match (self.position(c), policy) {
+                (Some(index), _) => {
+                    let symbol = self.symbol(shift(index));
+                    if self.preserve_case && c.is_lowercase() {
+                        result.push(symbol.to_ascii_lowercase());
+                    } else {
+                        result.push(symbol);
+                    }
+                }
+                (None, UnknownSymbol::PassThrough) => result.push(c),
+                (None, UnknownSymbol::Drop) => {}
+                (None, UnknownSymbol::Reject) => return Err(CipherError::InvalidInputSymbol(c)),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl fmt::Display for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == Self::uppercase() {
+            write!(f, "uppercase")
+        } else if *self == Self::letters() {
+            write!(f, "letters")
+        } else if *self == Self::printable_ascii() {
+            write!(f, "printable-ascii")
+        } else if *self == Self::bytes() {
+            write!(f, "bytes")
+        } else {
+            write!(f, "{}", self.symbols.iter().collect::<String>())
+        }
+    }
+}
+
+impl FromStr for Alphabet {
+    type Err = CipherError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uppercase" => Ok(Self::uppercase()),
+            "letters" => Ok(Self::letters()),
+            "printable-ascii" => Ok(Self::printable_ascii()),
+            "bytes" => Ok(Self::bytes()),
+            _ => Self::new(s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_alphabets() {
+        assert_eq!(Alphabet::uppercase().len(), 27);
+        assert_eq!(Alphabet::letters().len(), 26);
+        assert_eq!(Alphabet::printable_ascii().len(), 95);
+        assert_eq!(Alphabet::bytes().len(), 256);
+        assert_eq!(Alphabet::new("abc").unwrap().len(), 3);
+
+        assert_eq!(Alphabet::new(""), Err(CipherError::EmptyAlphabet));
+        assert_eq!(
+            Alphabet::new("abca"),
+            Err(CipherError::DuplicatedSymbol('a'))
+        );
+
+        assert_eq!(Alphabet::uppercase().position('h'), Some(8));
+        assert_eq!(Alphabet::new("abc").unwrap().position('A'), None);
+    }
+
+    #[test]
+    fn parse_alphabets() {
+        for alphabet in [
+            Alphabet::uppercase(),
+            Alphabet::letters(),
+            Alphabet::printable_ascii(),
+            Alphabet::bytes(),
+            Alphabet::new("0123456789").unwrap(),
+        ] {
+            assert_eq!(alphabet.to_string().parse::<Alphabet>(), Ok(alphabet));
+        }
+
+        assert_eq!("".parse::<Alphabet>(), Err(CipherError::EmptyAlphabet));
+        assert_eq!(Alphabet::uppercase().count("Hi, 42!"), 3);
+    }
+
+    #[test]
+    fn transform_unknown_symbols() {
+        let alphabet = Alphabet::uppercase();
+        let next = |i: usize| i + 1;
+
+        assert_eq!(
+            alphabet.transform("Hi, 42!", UnknownSymbol::PassThrough, next),
+            Ok("IJ,A42!".to_string())
+        );
+        assert_eq!(
+            alphabet.transform("Hi, 42!", UnknownSymbol::Drop, next),
+            Ok("IJA".to_string())
+        );
+        assert_eq!(
+            alphabet.transform("Hi, 42!", UnknownSymbol::Reject, next),
+            Err(CipherError::InvalidInputSymbol(','))
+        );
+
+        assert_eq!(
+            Alphabet::letters().transform("Hi, Zz!", UnknownSymbol::PassThrough, next),
+            Ok("Ij, Aa!".to_string())
+        );
+    }
+}
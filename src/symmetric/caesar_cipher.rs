@@ -1,5 +1,10 @@
+use crate::analysis;
+use crate::analysis::fitness::{self, Fitness, Quadgrams};
+use crate::cipher::{to_text, Cipher, KeyGen, StreamCipher};
+use crate::error::CipherError;
+use crate::symmetric::alphabet::{Alphabet, UnknownSymbol};
 use crate::symmetric::ALPHABET;
-use std::collections::HashMap;
+use rand::Rng;
 
 /*
     Basically, this encryption method shifts letters in the alphabet a given `N` times, `N` being a secret
@@ -17,28 +22,106 @@ use std::collections::HashMap;
 const MOST_FREQUENT_LETTERS_IN_ENGLISH_ALPHABET: [char; 12] =
     ['E', 'T', 'A', 'O', 'I', 'N', 'S', 'H', 'R', 'D', 'L', 'U'];
 
-pub fn encrypt(plain_text: &str, secret_key: i32) -> String {
-    let mut cipher_text = String::new();
-    let secret = secret_key.abs() as usize % ALPHABET.len();
-    for c in plain_text.to_uppercase().chars() {
-        let plain_text_index = ALPHABET.iter().position(|a| *a == c).unwrap_or_default();
-        let index = (plain_text_index + secret) % ALPHABET.len();
-        cipher_text.push(ALPHABET[index]);
+#[allow(dead_code)]
+pub fn encrypt(plain_text: &str, secret_key: i32) -> Result<String, CipherError> {
+    encrypt_with(
+        plain_text,
+        secret_key,
+        &Alphabet::default(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+pub fn decrypt(cipher_text: &str, secret_key: i32) -> Result<String, CipherError> {
+    decrypt_with(
+        cipher_text,
+        secret_key,
+        &Alphabet::default(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+// Shifts only letters keeping their case, everything else is left untouched.
+#[allow(dead_code)]
+pub fn encrypt_preserving_case(plain_text: &str, secret_key: i32) -> Result<String, CipherError> {
+    encrypt_with(
+        plain_text,
+        secret_key,
+        &Alphabet::letters(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+#[allow(dead_code)]
+pub fn decrypt_preserving_case(cipher_text: &str, secret_key: i32) -> Result<String, CipherError> {
+    decrypt_with(
+        cipher_text,
+        secret_key,
+        &Alphabet::letters(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+pub fn encrypt_with(
+    plain_text: &str,
+    secret_key: i32,
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    let secret = secret_key.unsigned_abs() as usize % alphabet.len();
+    alphabet.transform(plain_text, policy, |index| index + secret)
+}
+
+pub fn decrypt_with(
+    cipher_text: &str,
+    secret_key: i32,
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    let secret = secret_key.unsigned_abs() as usize % alphabet.len();
+    alphabet.transform(cipher_text, policy, |index| alphabet.len() + index - secret)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CaesarCipher {
+    pub alphabet: Alphabet,
+    pub policy: UnknownSymbol,
+}
+
+impl CaesarCipher {
+    pub fn new(alphabet: Alphabet, policy: UnknownSymbol) -> Self {
+        Self { alphabet, policy }
+    }
+}
+
+impl Cipher for CaesarCipher {
+    type Key = i32;
+
+    fn encrypt(&self, key: &i32, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        encrypt_with(to_text(plain_text)?, *key, &self.alphabet, self.policy)
+            .map(String::into_bytes)
     }
 
-    cipher_text
+    fn decrypt(&self, key: &i32, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        decrypt_with(to_text(cipher_text)?, *key, &self.alphabet, self.policy)
+            .map(String::into_bytes)
+    }
 }
 
-pub fn decrypt(cipher_text: &str, secret_key: i32) -> String {
-    let mut plain_text = String::new();
-    let secret = secret_key.abs() as usize % ALPHABET.len();
-    for c in cipher_text.chars() {
-        let cipher_text_char_index = ALPHABET.iter().position(|a| *a == c).unwrap_or_default();
-        let index = (ALPHABET.len() + cipher_text_char_index - secret) % ALPHABET.len();
-        plain_text.push(ALPHABET[index]);
+impl StreamCipher for CaesarCipher {
+    fn key_stream(&self, key: &i32, length: usize) -> Result<Vec<usize>, CipherError> {
+        Ok(vec![
+            key.unsigned_abs() as usize % self.alphabet.len();
+            length
+        ])
     }
+}
 
-    plain_text
+impl KeyGen for CaesarCipher {
+    // Shifting by 0 would leave the text untouched.
+    fn generate_key(&self) -> i32 {
+        rand::thread_rng().gen_range(1..self.alphabet.len()) as i32
+    }
 }
 
 /*
@@ -50,42 +133,30 @@ pub fn decrypt(cipher_text: &str, secret_key: i32) -> String {
     Frequency analysis: It will try to get the most frequent letters in the cipher text
     and compare with the most frequent letters in the english alphabet, generating a list of
     possible secrets to try and decrypt the message;
-*/
-
-#[allow(dead_code)]
-pub fn brute_force_attack(cipher_text: &str) -> Vec<String> {
-    let mut result = vec![];
 
-    for i in 0..ALPHABET.len() {
-        result.push(decrypt(cipher_text, i as i32));
-    }
+    Crack: It decrypts the message with every possible secret (brute force) and ranks the candidates
+    with a fitness, english quadgrams by default, the higher the score, the closer the candidate is to english,
+    so there is no need to read every output.
+*/
 
-    result
+pub fn brute_force_attack(cipher_text: &str) -> Vec<(usize, String)> {
+    (0..ALPHABET.len())
+        .filter_map(|secret| Some((secret, decrypt(cipher_text, secret as i32).ok()?)))
+        .collect()
 }
 
-fn frequencies(text: &str) -> HashMap<char, i32> {
-    let mut map = HashMap::new();
-    for c in text.to_uppercase().chars() {
-        if c != ' ' {
-            *map.entry(c).or_insert(0) += 1;
-        }
-    }
-    map
-}
-
-#[allow(dead_code)]
 pub fn frequency_analysis(cipher_text: &str) -> Vec<u8> {
-    let mut freq = frequencies(cipher_text)
-        .into_iter()
-        .collect::<Vec<(char, i32)>>();
-
-    freq.sort_by(|a, b| b.1.cmp(&a.1));
+    // spaces are left out, only the most frequent letter is compared
+    let counts = analysis::frequencies(cipher_text, &Alphabet::uppercase());
+    let most_frequent_letter = (1..ALPHABET.len())
+        .filter(|i| counts[*i] > 0)
+        .max_by_key(|i| (counts[*i], std::cmp::Reverse(*i)));
 
-    if let Some((most_frequent_letter, _)) = freq.get(0) {
+    if let Some(cipher_index) = most_frequent_letter {
         let mut result = vec![0u8; MOST_FREQUENT_LETTERS_IN_ENGLISH_ALPHABET.len()];
         for (i, c) in MOST_FREQUENT_LETTERS_IN_ENGLISH_ALPHABET.iter().enumerate() {
-            let ascii_number = *most_frequent_letter as u8;
-            result[i] = ascii_number.saturating_sub(*c as u8) % u8::MAX;
+            let plain_index = ALPHABET.iter().position(|a| a == c).unwrap_or_default();
+            result[i] = ((ALPHABET.len() + cipher_index - plain_index) % ALPHABET.len()) as u8;
         }
 
         result
@@ -94,44 +165,131 @@ pub fn frequency_analysis(cipher_text: &str) -> Vec<u8> {
     }
 }
 
+// Returns (secret, score, plain text) for every possible secret, the most likely one first.
+pub fn crack(cipher_text: &str) -> Vec<(usize, f64, String)> {
+    crack_with(cipher_text, Quadgrams::english())
+}
+
+pub fn crack_with(cipher_text: &str, fitness: &dyn Fitness) -> Vec<(usize, f64, String)> {
+    fitness::rank(brute_force_attack(cipher_text), fitness)
+}
+
+// Only the secrets suggested by frequency analysis are ranked, spaces are encrypted as well
+// so the most frequent symbol being a space is tried too.
+pub fn crack_by_frequency(cipher_text: &str) -> Vec<(usize, f64, String)> {
+    let counts = analysis::frequencies(cipher_text, &Alphabet::uppercase());
+    let space = (0..ALPHABET.len())
+        .filter(|i| counts[*i] > 0)
+        .max_by_key(|i| (counts[*i], std::cmp::Reverse(*i)));
+
+    let mut secrets = frequency_analysis(cipher_text)
+        .into_iter()
+        .map(usize::from)
+        .collect::<Vec<usize>>();
+    if let Some(space) = space.filter(|s| !secrets.contains(s)) {
+        secrets.push(space);
+    }
+
+    let candidates = secrets
+        .into_iter()
+        .filter_map(|secret| Some((secret, decrypt(cipher_text, secret as i32).ok()?)));
+    fitness::rank(candidates, Quadgrams::english())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::analysis::fitness::ChiSquared;
+    use crate::analysis::Language;
     #[test]
     fn encrypt_decrypt_message() {
         let message = "Hello World";
         let secret = 3;
-        let result = encrypt(&message, secret);
+        let result = encrypt(message, secret).unwrap();
         assert_eq!(result, "KHOORCZRUOG");
-        let decrypted = decrypt(&result, secret);
+        let decrypted = decrypt(&result, secret).unwrap();
         assert_eq!(decrypted, "HELLO WORLD");
 
         // Test overflow case
         let secret = 80;
-        let result = encrypt(message, secret);
+        let result = encrypt(message, secret).unwrap();
         assert_eq!(result, "GDKKNZVNQKC");
-        let decrypted = decrypt(&result, secret);
+        let decrypted = decrypt(&result, secret).unwrap();
         assert_eq!(decrypted, "HELLO WORLD");
 
         let secret = 200;
-        let result = encrypt(message, secret);
+        let result = encrypt(message, secret).unwrap();
         assert_eq!(result, "SPWWZKGZBWO");
-        let decrypted = decrypt(&result, secret);
+        let decrypted = decrypt(&result, secret).unwrap();
         assert_eq!(decrypted, "HELLO WORLD");
     }
 
+    #[test]
+    fn encrypt_decrypt_with_alphabet() {
+        let message = "Hello, World! 123";
+        let result = encrypt(message, 3).unwrap();
+        assert_eq!(result, "KHOOR,CZRUOG!C123");
+        assert_eq!(decrypt(&result, 3).unwrap(), "HELLO, WORLD! 123");
+
+        let alphabet = Alphabet::printable_ascii();
+        let result = encrypt_with(message, 3, &alphabet, UnknownSymbol::Reject).unwrap();
+        assert_eq!(result, "Khoor/#Zruog$#456");
+        let decrypted = decrypt_with(&result, 3, &alphabet, UnknownSymbol::Reject).unwrap();
+        assert_eq!(decrypted, message);
+
+        let alphabet = Alphabet::new("0123456789").unwrap();
+        let result = encrypt_with(message, 3, &alphabet, UnknownSymbol::Drop).unwrap();
+        assert_eq!(result, "456");
+        assert_eq!(
+            encrypt_with(message, 3, &alphabet, UnknownSymbol::Reject),
+            Err(CipherError::InvalidInputSymbol('H'))
+        );
+    }
+
+    #[test]
+    fn encrypt_decrypt_preserving_case() {
+        let message = "Hello, World!";
+        let result = encrypt_preserving_case(message, 3).unwrap();
+        assert_eq!(result, "Khoor, Zruog!");
+        assert_eq!(decrypt_preserving_case(&result, 3).unwrap(), message);
+
+        let result = encrypt_preserving_case("xyz ABC", 29).unwrap();
+        assert_eq!(result, "abc DEF");
+    }
+
+    #[test]
+    fn encrypt_decrypt_through_traits() {
+        let cipher = CaesarCipher::new(Alphabet::letters(), UnknownSymbol::PassThrough);
+        let encrypted = Cipher::encrypt(&cipher, &3, b"Hello, World!").unwrap();
+        assert_eq!(encrypted, b"Khoor, Zruog!");
+        assert_eq!(
+            Cipher::decrypt(&cipher, &3, &encrypted).unwrap(),
+            b"Hello, World!"
+        );
+        assert_eq!(cipher.key_stream(&29, 3).unwrap(), vec![3, 3, 3]);
+
+        let key = cipher.generate_key();
+        assert!((1..26).contains(&key));
+
+        assert_eq!(
+            Cipher::encrypt(&cipher, &3, &[0xff, 0xfe]),
+            Err(CipherError::InvalidEncoding)
+        );
+    }
+
     #[test]
     fn brute_force_attack_encrypted_message() {
         let message = "KHOORCZRUOG";
         let decrypted = brute_force_attack(message);
 
-        for i in 0..ALPHABET.len() {
+        for (i, (secret, candidate)) in decrypted.iter().enumerate() {
+            assert_eq!(i, *secret);
             match i {
                 3 => {
-                    assert_eq!(decrypted[i], "HELLO WORLD");
+                    assert_eq!(candidate, "HELLO WORLD");
                 }
                 _ => {
-                    assert_ne!(decrypted[i], "HELLO WORLD");
+                    assert_ne!(candidate, "HELLO WORLD");
                 }
             }
         }
@@ -141,14 +299,58 @@ mod test {
     fn frequency_analysis_attack() {
         let cipher_text = "KHOORCZRUOG";
         let result = frequency_analysis(cipher_text);
-        for i in 0..MOST_FREQUENT_LETTERS_IN_ENGLISH_ALPHABET.len() {
+        for (i, secret) in result.iter().enumerate() {
             if i == 10 {
-                assert_eq!(decrypt(cipher_text, result[i] as i32), "HELLO WORLD");
+                assert_eq!(decrypt(cipher_text, *secret as i32).unwrap(), "HELLO WORLD");
             } else {
-                assert_ne!(decrypt(cipher_text, result[i] as i32), "HELLO WORLD");
+                assert_ne!(decrypt(cipher_text, *secret as i32).unwrap(), "HELLO WORLD");
             }
         }
 
         assert_eq!(frequency_analysis(""), vec![]);
+
+        // shifts wrapping around the alphabet
+        let cipher_text = encrypt("HELLO WORLD", 20).unwrap();
+        let result = frequency_analysis(&cipher_text);
+        assert_eq!(
+            decrypt(&cipher_text, result[10] as i32).unwrap(),
+            "HELLO WORLD"
+        );
+    }
+
+    #[test]
+    fn crack_by_frequency_analysis() {
+        let message = "MEET ME AT THE USUAL PLACE AT TEN RATHER THAN EIGHT";
+        let cipher_text = encrypt(message, 5).unwrap();
+        let result = crack_by_frequency(&cipher_text);
+        assert_eq!(result.len(), 13);
+        assert_eq!((result[0].0, result[0].2.as_str()), (5, message));
+
+        assert_eq!(crack_by_frequency(""), vec![]);
+    }
+
+    #[test]
+    fn crack_encrypted_message() {
+        let message = "MEET ME AT THE USUAL PLACE AT TEN RATHER THAN EIGHT";
+        for secret in [3, 13, 26] {
+            let cipher_text = encrypt(message, secret).unwrap();
+            let result = crack(&cipher_text);
+            assert_eq!(result.len(), ALPHABET.len());
+            assert_eq!(result[0].0, secret as usize);
+            assert_eq!(result[0].2, message);
+            assert!(result.windows(2).all(|w| w[0].1 >= w[1].1));
+        }
+
+        assert_eq!(crack("").len(), ALPHABET.len());
+
+        let result = crack_with(
+            &encrypt(message, 7).unwrap(),
+            &ChiSquared(Language::English),
+        );
+        assert_eq!(result[0].0, 7);
+        assert_eq!(
+            crack(&encrypt("HELLO WORLD", 7).unwrap())[0].2,
+            "HELLO WORLD"
+        );
     }
 }
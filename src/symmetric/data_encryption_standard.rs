@@ -67,4 +67,723 @@
 
 */
 
-// TODO - Implement DAS algorithm by myself
+use crate::cipher::{BlockCipher, Cipher, KeyGen};
+use crate::error::CipherError;
+use crate::symmetric::trace::Trace;
+use rand::Rng;
+
+pub const KEY_LENGTH: usize = 8;
+pub const BLOCK_LENGTH: usize = 8;
+pub const ROUNDS: usize = 16;
+
+// Every table numbers the bits from 1 (the most significant one) like the standard (FIPS 46-3) does.
+
+const INITIAL_PERMUTATION: [u8; 64] = [
+    58, 50, 42, 34, 26, 18, 10, 2, 60, 52, 44, 36, 28, 20, 12, 4, //
+    62, 54, 46, 38, 30, 22, 14, 6, 64, 56, 48, 40, 32, 24, 16, 8, //
+    57, 49, 41, 33, 25, 17, 9, 1, 59, 51, 43, 35, 27, 19, 11, 3, //
+    61, 53, 45, 37, 29, 21, 13, 5, 63, 55, 47, 39, 31, 23, 15, 7,
+];
+
+const FINAL_PERMUTATION: [u8; 64] = [
+    40, 8, 48, 16, 56, 24, 64, 32, 39, 7, 47, 15, 55, 23, 63, 31, //
+    38, 6, 46, 14, 54, 22, 62, 30, 37, 5, 45, 13, 53, 21, 61, 29, //
+    36, 4, 44, 12, 52, 20, 60, 28, 35, 3, 43, 11, 51, 19, 59, 27, //
+    34, 2, 42, 10, 50, 18, 58, 26, 33, 1, 41, 9, 49, 17, 57, 25,
+];
+
+const EXPANSION: [u8; 48] = [
+    32, 1, 2, 3, 4, 5, 4, 5, 6, 7, 8, 9, //
+    8, 9, 10, 11, 12, 13, 12, 13, 14, 15, 16, 17, //
+    16, 17, 18, 19, 20, 21, 20, 21, 22, 23, 24, 25, //
+    24, 25, 26, 27, 28, 29, 28, 29, 30, 31, 32, 1,
+];
+
+const PERMUTATION: [u8; 32] = [
+    16, 7, 20, 21, 29, 12, 28, 17, 1, 15, 23, 26, 5, 18, 31, 10, //
+    2, 8, 24, 14, 32, 27, 3, 9, 19, 13, 30, 6, 22, 11, 4, 25,
+];
+
+// The parity bits (8, 16, ..., 64) are left out.
+const PERMUTED_CHOICE_1: [u8; 56] = [
+    57, 49, 41, 33, 25, 17, 9, 1, 58, 50, 42, 34, 26, 18, //
+    10, 2, 59, 51, 43, 35, 27, 19, 11, 3, 60, 52, 44, 36, //
+    63, 55, 47, 39, 31, 23, 15, 7, 62, 54, 46, 38, 30, 22, //
+    14, 6, 61, 53, 45, 37, 29, 21, 13, 5, 28, 20, 12, 4,
+];
+
+const PERMUTED_CHOICE_2: [u8; 48] = [
+    14, 17, 11, 24, 1, 5, 3, 28, 15, 6, 21, 10, //
+    23, 19, 12, 4, 26, 8, 16, 7, 27, 20, 13, 2, //
+    41, 52, 31, 37, 47, 55, 30, 40, 51, 45, 33, 48, //
+    44, 49, 39, 56, 34, 53, 46, 42, 50, 36, 29, 32,
+];
+
+const SHIFTS: [u32; ROUNDS] = [1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1];
+
+// 8 boxes of 4 rows by 16 columns.
+const S_BOXES: [[u8; 64]; 8] = [
+    [
+        14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7, //
+        0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8, //
+        4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0, //
+        15, 12, 8, 2, 4, 9, 1, 7, 5, 11, 3, 14, 10, 0, 6, 13,
+    ],
+    [
+        15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10, //
+        3, 13, 4, 7, 15, 2, 8, 14, 12, 0, 1, 10, 6, 9, 11, 5, //
+        0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15, //
+        13, 8, 10, 1, 3, 15, 4, 2, 11, 6, 7, 12, 0, 5, 14, 9,
+    ],
+    [
+        10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8, //
+        13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5, 14, 12, 11, 15, 1, //
+        13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7, //
+        1, 10, 13, 0, 6, 9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12,
+    ],
+    [
+        7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15, //
+        13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2, 12, 1, 10, 14, 9, //
+        10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4, //
+        3, 15, 0, 6, 10, 1, 13, 8, 9, 4, 5, 11, 12, 7, 2, 14,
+    ],
+    [
+        2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9, //
+        14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15, 10, 3, 9, 8, 6, //
+        4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14, //
+        11, 8, 12, 7, 1, 14, 2, 13, 6, 15, 0, 9, 10, 4, 5, 3,
+    ],
+    [
+        12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11, //
+        10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13, 14, 0, 11, 3, 8, //
+        9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6, //
+        4, 3, 2, 12, 9, 5, 15, 10, 11, 14, 1, 7, 6, 0, 8, 13,
+    ],
+    [
+        4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1, //
+        13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5, 12, 2, 15, 8, 6, //
+        1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2, //
+        6, 11, 13, 8, 1, 4, 10, 7, 9, 5, 0, 15, 14, 2, 3, 12,
+    ],
+    [
+        13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7, //
+        1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6, 11, 0, 14, 9, 2, //
+        7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8, //
+        2, 1, 14, 7, 4, 10, 8, 13, 15, 12, 9, 0, 3, 5, 6, 11,
+    ],
+];
+
+// Output bit i is the bit table[i] of the input, `width` is the number of bits of the input.
+fn permute(input: u64, table: &[u8], width: u32) -> u64 {
+    table.iter().fold(0, |output, position| {
+        (output << 1) | ((input >> (width - *position as u32)) & 1)
+    })
+}
+
+// Circular left shift of a 28 bit half of the key.
+fn rotate(half: u64, shift: u32) -> u64 {
+    ((half << shift) | (half >> (28 - shift))) & 0x0FFF_FFFF
+}
+
+// The 16 sub-keys of 48 bits in the order they are used to encrypt.
+pub fn sub_keys(key: u64) -> [u64; ROUNDS] {
+    let key = permute(key, &PERMUTED_CHOICE_1, 64);
+    let (mut left, mut right) = (key >> 28, key & 0x0FFF_FFFF);
+
+    let mut sub_keys = [0; ROUNDS];
+    for (sub_key, shift) in sub_keys.iter_mut().zip(SHIFTS) {
+        left = rotate(left, shift);
+        right = rotate(right, shift);
+        *sub_key = permute((left << 28) | right, &PERMUTED_CHOICE_2, 56);
+    }
+    sub_keys
+}
+
+// Each group of 6 bits picks a row (first and last bits) and a column (middle bits) of its box.
+fn substitute(input: u64) -> u64 {
+    S_BOXES.iter().enumerate().fold(0, |output, (i, s_box)| {
+        let bits = (input >> (42 - 6 * i)) & 0x3F;
+        let row = ((bits & 0x20) >> 4) | (bits & 1);
+        let column = (bits >> 1) & 0x0F;
+        (output << 4) | s_box[(row * 16 + column) as usize] as u64
+    })
+}
+
+pub fn process_block(block: u64, sub_keys: &[u64; ROUNDS], decrypting: bool) -> u64 {
+    run_rounds(block, sub_keys, decrypting, &mut |_, _, _, _| {})
+}
+
+// Same as process_block, recording every intermediate value.
+pub fn trace_block(
+    block: u64,
+    sub_keys: &[u64; ROUNDS],
+    decrypting: bool,
+    trace: &mut Trace,
+) -> u64 {
+    run_rounds(
+        block,
+        sub_keys,
+        decrypting,
+        &mut |round, name, value, bits| trace.record_bits(round, name, value, bits),
+    )
+}
+
+// `record` receives the round, the name of the step, the value and its size in bits.
+fn run_rounds(
+    block: u64,
+    sub_keys: &[u64; ROUNDS],
+    decrypting: bool,
+    record: &mut impl FnMut(usize, &'static str, u64, usize),
+) -> u64 {
+    record(0, "input", block, 64);
+    let block = permute(block, &INITIAL_PERMUTATION, 64);
+    record(0, "ip", block, 64);
+    let (mut left, mut right) = (block >> 32, block & 0xFFFF_FFFF);
+
+    for round in 1..=ROUNDS {
+        let sub_key = if decrypting {
+            sub_keys[ROUNDS - round]
+        } else {
+            sub_keys[round - 1]
+        };
+        record(round, "k_sch", sub_key, 48);
+
+        // Feistel function: expansion, xor with the sub-key, S-boxes and permutation.
+        let expanded = permute(right, &EXPANSION, 32);
+        record(round, "expand", expanded, 48);
+        let substituted = substitute(expanded ^ sub_key);
+        record(round, "s_box", substituted, 32);
+        let permuted = permute(substituted, &PERMUTATION, 32);
+        record(round, "p", permuted, 32);
+
+        (left, right) = (right, left ^ permuted);
+        record(round, "left", left, 32);
+        record(round, "right", right, 32);
+    }
+
+    // the halves are swapped one last time before the final permutation
+    let output = permute((right << 32) | left, &FINAL_PERMUTATION, 64);
+    record(ROUNDS, "output", output, 64);
+    output
+}
+
+pub fn encrypt_block(block: u64, key: u64) -> u64 {
+    process_block(block, &sub_keys(key), false)
+}
+
+pub fn decrypt_block(block: u64, key: u64) -> u64 {
+    process_block(block, &sub_keys(key), true)
+}
+
+pub fn to_u64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |value, b| (value << 8) | *b as u64)
+}
+
+/*
+    Weak keys and parity
+    PC-1 drops the last bit of every key byte, those 8 bits are parity bits: each byte should have an odd number of ones.
+    DES never looks at them, so keys differing only in the parity bits encrypt the same way,
+    checking them only catches keys that were typed or transmitted wrong.
+
+    A few keys give sub-keys that are all equal (the 28 bit halves are all zeros or all ones),
+    with them encrypting and decrypting are the same operation:
+    -> Weak keys (4): encrypting twice gives the plain text back.
+    -> Semi-weak keys (12, in 6 pairs): one key of the pair decrypts what the other encrypts.
+    They are rejected, the chance of picking one at random is 16 in 2^56.
+*/
+
+// Every bit but the parity bits.
+pub const KEY_BITS: u64 = 0xFEFE_FEFE_FEFE_FEFE;
+
+const WEAK_KEYS: [u64; 4] = [
+    0x0101010101010101,
+    0xFEFEFEFEFEFEFEFE,
+    0xE0E0E0E0F1F1F1F1,
+    0x1F1F1F1F0E0E0E0E,
+];
+
+const SEMI_WEAK_KEYS: [(u64, u64); 6] = [
+    (0x01FE01FE01FE01FE, 0xFE01FE01FE01FE01),
+    (0x1FE01FE00EF10EF1, 0xE01FE01FF10EF10E),
+    (0x01E001E001F101F1, 0xE001E001F101F101),
+    (0x1FFE1FFE0EFE0EFE, 0xFE1FFE1FFE0EFE0E),
+    (0x011F011F010E010E, 0x1F011F010E010E01),
+    (0xE0FEE0FEF1FEF1FE, 0xFEE0FEE0FEF1FEF1),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Parity {
+    #[default]
+    Ignore,
+    Check,
+}
+
+pub fn has_odd_parity(key: u64) -> bool {
+    key.to_be_bytes().iter().all(|b| b.count_ones() % 2 == 1)
+}
+
+// Sets the last bit of every byte so it has an odd number of ones, the effective key doesn't change.
+pub fn fix_parity(key: u64) -> u64 {
+    u64::from_be_bytes(key.to_be_bytes().map(|b| {
+        let b = b & 0xFE;
+        b | (b.count_ones() % 2 == 0) as u8
+    }))
+}
+
+pub fn check_parity(key: u64) -> Result<(), CipherError> {
+    if !has_odd_parity(key) {
+        return Err(CipherError::InvalidParity);
+    }
+    Ok(())
+}
+
+// Weak and semi-weak keys are compared without the parity bits.
+pub fn check_weakness(key: u64) -> Result<(), CipherError> {
+    let key = key & KEY_BITS;
+    if WEAK_KEYS.iter().any(|weak| weak & KEY_BITS == key) {
+        return Err(CipherError::WeakKey);
+    }
+    for (a, b) in SEMI_WEAK_KEYS {
+        if a & KEY_BITS == key {
+            return Err(CipherError::SemiWeakKey { pair: b });
+        }
+        if b & KEY_BITS == key {
+            return Err(CipherError::SemiWeakKey { pair: a });
+        }
+    }
+    Ok(())
+}
+
+fn check_key(key: &[u8], parity: Parity) -> Result<u64, CipherError> {
+    if key.len() != KEY_LENGTH {
+        return Err(CipherError::InvalidKeyLength {
+            expected: KEY_LENGTH,
+            actual: key.len(),
+        });
+    }
+    let key = to_u64(key);
+    if parity == Parity::Check {
+        check_parity(key)?;
+    }
+    check_weakness(key)?;
+    Ok(key)
+}
+
+/*
+    Modes of operation
+    DES only encrypts blocks of 8 bytes, to encrypt longer messages the blocks are chained (CBC):
+    each plain text block is xored with the previous cipher block (the IV for the first one) before being encrypted,
+    so equal blocks don't produce equal cipher blocks.
+    The last block is completed with PKCS#7 padding: N bytes with the value N, a full block when the data
+    is already a multiple of 8, that way the padding can always be removed.
+*/
+
+pub fn cbc_encrypt(data: &[u8], iv: u64, mut encrypt_block: impl FnMut(u64) -> u64) -> Vec<u8> {
+    let padding = BLOCK_LENGTH - data.len() % BLOCK_LENGTH;
+    let mut data = data.to_vec();
+    data.extend(std::iter::repeat_n(padding as u8, padding));
+
+    let mut previous = iv;
+    data.chunks(BLOCK_LENGTH)
+        .flat_map(|block| {
+            previous = encrypt_block(to_u64(block) ^ previous);
+            previous.to_be_bytes()
+        })
+        .collect()
+}
+
+pub fn cbc_decrypt(
+    data: &[u8],
+    iv: u64,
+    mut decrypt_block: impl FnMut(u64) -> u64,
+) -> Result<Vec<u8>, CipherError> {
+    if data.is_empty() || !data.len().is_multiple_of(BLOCK_LENGTH) {
+        return Err(CipherError::InvalidEncoding);
+    }
+
+    let mut previous = iv;
+    let mut result = data
+        .chunks(BLOCK_LENGTH)
+        .flat_map(|block| {
+            let block = to_u64(block);
+            let plain = decrypt_block(block) ^ previous;
+            previous = block;
+            plain.to_be_bytes()
+        })
+        .collect::<Vec<u8>>();
+
+    let padding = *result.last().unwrap_or(&0) as usize;
+    if padding == 0
+        || padding > BLOCK_LENGTH
+        || result[result.len() - padding..]
+            .iter()
+            .any(|b| *b as usize != padding)
+    {
+        return Err(CipherError::InvalidPadding);
+    }
+    result.truncate(result.len() - padding);
+    Ok(result)
+}
+
+pub fn check_iv(iv: &[u8]) -> Result<u64, CipherError> {
+    if iv.len() != BLOCK_LENGTH {
+        return Err(CipherError::InvalidIvLength {
+            expected: BLOCK_LENGTH,
+            actual: iv.len(),
+        });
+    }
+    Ok(to_u64(iv))
+}
+
+// DES/CBC/PKCS#7, the result is hex encoded.
+#[allow(dead_code)]
+pub fn encrypt(data: &[u8], key: &[u8], iv: &[u8]) -> Result<String, CipherError> {
+    encrypt_bytes(data, key, iv, Parity::Ignore).map(hex::encode)
+}
+
+#[allow(dead_code)]
+pub fn decrypt(encrypted_data: &str, key: &[u8], iv: &[u8]) -> Result<String, CipherError> {
+    let encrypted_data = hex::decode(encrypted_data).map_err(|_| CipherError::InvalidEncoding)?;
+    let decrypted_data = decrypt_bytes(&encrypted_data, key, iv, Parity::Ignore)?;
+    String::from_utf8(decrypted_data).map_err(|_| CipherError::InvalidEncoding)
+}
+
+// One trace per block of the DES/CBC/PKCS#7 encryption, the key is checked like in the encryption.
+pub fn trace(
+    data: &[u8],
+    key: &[u8],
+    iv: &[u8],
+    parity: Parity,
+) -> Result<Vec<Trace>, CipherError> {
+    let sub_keys = sub_keys(check_key(key, parity)?);
+    let iv = check_iv(iv)?;
+    let mut traces = vec![];
+    cbc_encrypt(data, iv, |block| {
+        let mut trace = Trace::new("DES", traces.len());
+        let output = trace_block(block, &sub_keys, false, &mut trace);
+        traces.push(trace);
+        output
+    });
+    Ok(traces)
+}
+
+// One trace per block of the decryption, the sub-keys are used from the 16th to the first
+// and the output of each trace is xored with the previous cipher block afterwards.
+pub fn trace_decryption(
+    data: &[u8],
+    key: &[u8],
+    iv: &[u8],
+    parity: Parity,
+) -> Result<Vec<Trace>, CipherError> {
+    let sub_keys = sub_keys(check_key(key, parity)?);
+    let iv = check_iv(iv)?;
+    let mut traces = vec![];
+    cbc_decrypt(data, iv, |block| {
+        let mut trace = Trace::new("DES", traces.len());
+        let output = trace_block(block, &sub_keys, true, &mut trace);
+        traces.push(trace);
+        output
+    })?;
+    Ok(traces)
+}
+
+fn encrypt_bytes(
+    data: &[u8],
+    key: &[u8],
+    iv: &[u8],
+    parity: Parity,
+) -> Result<Vec<u8>, CipherError> {
+    let sub_keys = sub_keys(check_key(key, parity)?);
+    let iv = check_iv(iv)?;
+    Ok(cbc_encrypt(data, iv, |block| {
+        process_block(block, &sub_keys, false)
+    }))
+}
+
+fn decrypt_bytes(
+    data: &[u8],
+    key: &[u8],
+    iv: &[u8],
+    parity: Parity,
+) -> Result<Vec<u8>, CipherError> {
+    let sub_keys = sub_keys(check_key(key, parity)?);
+    let iv = check_iv(iv)?;
+    cbc_decrypt(data, iv, |block| process_block(block, &sub_keys, true))
+}
+
+pub struct Des {
+    iv: [u8; BLOCK_LENGTH],
+    parity: Parity,
+}
+
+impl Des {
+    pub fn new(iv: [u8; BLOCK_LENGTH]) -> Self {
+        Self {
+            iv,
+            parity: Parity::default(),
+        }
+    }
+
+    pub fn with_parity(self, parity: Parity) -> Self {
+        Self { parity, ..self }
+    }
+}
+
+impl Cipher for Des {
+    type Key = Vec<u8>;
+
+    fn encrypt(&self, key: &Vec<u8>, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        encrypt_bytes(plain_text, key, &self.iv, self.parity)
+    }
+
+    fn decrypt(&self, key: &Vec<u8>, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        decrypt_bytes(cipher_text, key, &self.iv, self.parity)
+    }
+}
+
+// A single block without chaining or padding.
+impl BlockCipher for Des {
+    const BLOCK_SIZE: usize = BLOCK_LENGTH;
+
+    fn encrypt_block(&self, key: &Vec<u8>, block: &[u8]) -> Result<Vec<u8>, CipherError> {
+        let key = check_key(key, self.parity)?;
+        if block.len() != BLOCK_LENGTH {
+            return Err(CipherError::InvalidBlock);
+        }
+        Ok(encrypt_block(to_u64(block), key).to_be_bytes().to_vec())
+    }
+
+    fn decrypt_block(&self, key: &Vec<u8>, block: &[u8]) -> Result<Vec<u8>, CipherError> {
+        let key = check_key(key, self.parity)?;
+        if block.len() != BLOCK_LENGTH {
+            return Err(CipherError::InvalidBlock);
+        }
+        Ok(decrypt_block(to_u64(block), key).to_be_bytes().to_vec())
+    }
+}
+
+// Keys with odd parity, generated again in the unlikely case of a weak or semi-weak key.
+impl KeyGen for Des {
+    fn generate_key(&self) -> Vec<u8> {
+        loop {
+            let key = fix_parity(rand::thread_rng().gen());
+            if check_weakness(key).is_ok() {
+                return key.to_be_bytes().to_vec();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_answer_tests() {
+        // NIST SP 800-17 (key, plain text, cipher text): variable plain text, variable key,
+        // permutation operation and substitution table tests
+        let vectors: [(u64, u64, u64); 7] = [
+            (0x0101010101010101, 0x8000000000000000, 0x95F8A5E5DD31D900),
+            (0x0101010101010101, 0x4000000000000000, 0xDD7F121CA5015619),
+            (0x0101010101010101, 0x2000000000000000, 0x2E8653104F3834EA),
+            (0x8001010101010101, 0x0000000000000000, 0x95A8D72813DAA94D),
+            (0x4001010101010101, 0x0000000000000000, 0x0EEC1487DD8C26D5),
+            (0x1046913489980131, 0x0000000000000000, 0x88D55E54F54C97B4),
+            (0x7CA110454A1A6E57, 0x01A1D6D039776742, 0x690F5B0D9A26939B),
+        ];
+
+        for (key, plain, cipher) in vectors {
+            assert_eq!(encrypt_block(plain, key), cipher, "key {:016X}", key);
+            assert_eq!(decrypt_block(cipher, key), plain, "key {:016X}", key);
+        }
+
+        // worked example from the standard's walkthrough
+        assert_eq!(
+            encrypt_block(0x0123456789ABCDEF, 0x133457799BBCDFF1),
+            0x85E813540F0AB405
+        );
+    }
+
+    #[test]
+    fn trace_rounds() {
+        // intermediate values of the worked example (J. Orlin Grabbe, The DES Algorithm Illustrated)
+        let mut block_trace = Trace::new("DES", 0);
+        let output = trace_block(
+            0x0123456789ABCDEF,
+            &sub_keys(0x133457799BBCDFF1),
+            false,
+            &mut block_trace,
+        );
+        assert_eq!(output, 0x85E813540F0AB405);
+        assert_eq!(block_trace.value(0, "ip"), Some("cc00ccfff0aaf0aa"));
+        assert_eq!(block_trace.value(1, "k_sch"), Some("1b02effc7072"));
+        assert_eq!(block_trace.value(1, "expand"), Some("7a15557a1555"));
+        assert_eq!(block_trace.value(1, "s_box"), Some("5c82b597"));
+        assert_eq!(block_trace.value(1, "p"), Some("234aa9bb"));
+        assert_eq!(block_trace.value(1, "left"), Some("f0aaf0aa"));
+        assert_eq!(block_trace.value(1, "right"), Some("ef4a6544"));
+        assert_eq!(block_trace.value(16, "left"), Some("43423234"));
+        assert_eq!(block_trace.value(16, "right"), Some("0a4cd995"));
+        assert_eq!(block_trace.value(16, "output"), Some("85e813540f0ab405"));
+        assert_eq!(block_trace.steps.len(), 2 + 16 * 6 + 1);
+
+        // decrypting runs the same rounds with the sub-keys in reverse order
+        let mut block_trace = Trace::new("DES", 0);
+        let output = trace_block(
+            0x85E813540F0AB405,
+            &sub_keys(0x133457799BBCDFF1),
+            true,
+            &mut block_trace,
+        );
+        assert_eq!(output, 0x0123456789ABCDEF);
+        assert_eq!(block_trace.value(1, "k_sch"), Some("cb3d8b0e17f5"));
+        assert_eq!(block_trace.value(16, "k_sch"), Some("1b02effc7072"));
+
+        // every block of the message, the last one ends with the cipher text
+        let traces = trace(b"Hello World", b"secret!!", b"00000000", Parity::Ignore).unwrap();
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[1].block, 1);
+        assert_eq!(traces[1].value(16, "output"), Some("03e8accd7a26c693"));
+        let encrypted =
+            encrypt_bytes(b"Hello World", b"secret!!", b"00000000", Parity::Ignore).unwrap();
+        let traces =
+            trace_decryption(&encrypted, b"secret!!", b"00000000", Parity::Ignore).unwrap();
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[1].value(0, "input"), Some("03e8accd7a26c693"));
+        assert_eq!(
+            trace(b"Hello World", b"secret!!", b"00000000", Parity::Check),
+            Err(CipherError::InvalidParity)
+        );
+    }
+
+    #[test]
+    fn key_schedule() {
+        let sub_keys = sub_keys(0x133457799BBCDFF1);
+        assert_eq!(
+            sub_keys[0],
+            0b000110_110000_001011_101111_111111_000111_000001_110010
+        );
+        assert_eq!(
+            sub_keys[15],
+            0b110010_110011_110110_001011_000011_100001_011111_110101
+        );
+    }
+
+    #[test]
+    fn encrypt_decrypt_message() {
+        let key = b"secret!!";
+        let iv = b"00000000";
+
+        // same as openssl enc -des-cbc
+        let encrypted = encrypt(b"Hello World", key, iv).unwrap();
+        assert_eq!(encrypted, "a6a7d9c8ffdac01403e8accd7a26c693");
+        assert_eq!(decrypt(&encrypted, key, iv).unwrap(), "Hello World");
+
+        // a full block of padding is added when the data fills the last block
+        let cipher = Des::new(*iv);
+        let encrypted = Cipher::encrypt(&cipher, &key.to_vec(), b"12345678").unwrap();
+        assert_eq!(encrypted.len(), 16);
+        assert_eq!(
+            Cipher::decrypt(&cipher, &key.to_vec(), &encrypted).unwrap(),
+            b"12345678"
+        );
+
+        let key = cipher.generate_key();
+        let block = cipher.encrypt_block(&key, b"abcdefgh").unwrap();
+        assert_eq!(cipher.decrypt_block(&key, &block).unwrap(), b"abcdefgh");
+    }
+
+    #[test]
+    fn reject_invalid_input() {
+        let key = b"secret!!";
+        let iv = b"00000000";
+
+        assert_eq!(
+            encrypt(b"Hello World", b"short", iv),
+            Err(CipherError::InvalidKeyLength {
+                expected: 8,
+                actual: 5
+            })
+        );
+        assert_eq!(
+            encrypt(b"Hello World", key, b"0"),
+            Err(CipherError::InvalidIvLength {
+                expected: 8,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            decrypt("not hex", key, iv),
+            Err(CipherError::InvalidEncoding)
+        );
+        assert_eq!(decrypt("0011", key, iv), Err(CipherError::InvalidEncoding));
+
+        let encrypted = encrypt(b"Hello World", key, iv).unwrap();
+        assert_eq!(
+            decrypt(&encrypted, b"secret??", iv),
+            Err(CipherError::InvalidPadding)
+        );
+        assert_eq!(
+            Des::new(*iv).encrypt_block(&key.to_vec(), b"short"),
+            Err(CipherError::InvalidBlock)
+        );
+    }
+
+    #[test]
+    fn weak_keys() {
+        let iv = b"00000000";
+        for key in WEAK_KEYS {
+            // every sub-key is the same, so encrypting twice gives the plain text back
+            assert_eq!(
+                encrypt_block(encrypt_block(0x0123456789ABCDEF, key), key),
+                0x0123456789ABCDEF
+            );
+            assert_eq!(
+                encrypt(b"data", &key.to_be_bytes(), iv),
+                Err(CipherError::WeakKey)
+            );
+        }
+        for (a, b) in SEMI_WEAK_KEYS {
+            assert_eq!(
+                decrypt_block(encrypt_block(0x0123456789ABCDEF, a), b),
+                encrypt_block(encrypt_block(0x0123456789ABCDEF, a), a)
+            );
+            assert_eq!(
+                encrypt_block(encrypt_block(0x0123456789ABCDEF, a), b),
+                0x0123456789ABCDEF
+            );
+            assert_eq!(check_weakness(a), Err(CipherError::SemiWeakKey { pair: b }));
+            assert_eq!(check_weakness(b), Err(CipherError::SemiWeakKey { pair: a }));
+        }
+
+        // the parity bits don't change the key
+        assert_eq!(
+            check_weakness(0x0000000000000000),
+            Err(CipherError::WeakKey)
+        );
+        assert_eq!(check_weakness(0x133457799BBCDFF1), Ok(()));
+    }
+
+    #[test]
+    fn key_parity() {
+        assert!(has_odd_parity(0x133457799BBCDFF1));
+        assert!(!has_odd_parity(to_u64(b"secret!!")));
+        assert_eq!(fix_parity(0x0000000000000000), 0x0101010101010101);
+        assert_eq!(fix_parity(0x123456789ABCDEF0), 0x133457799BBCDFF1);
+
+        let key = fix_parity(to_u64(b"secret!!"));
+        assert!(has_odd_parity(key));
+        assert_eq!(
+            encrypt_block(0x0123456789ABCDEF, key),
+            encrypt_block(0x0123456789ABCDEF, to_u64(b"secret!!"))
+        );
+
+        let cipher = Des::new(*b"00000000").with_parity(Parity::Check);
+        assert_eq!(
+            Cipher::encrypt(&cipher, &b"secret!!".to_vec(), b"data"),
+            Err(CipherError::InvalidParity)
+        );
+        assert!(Cipher::encrypt(&cipher, &key.to_be_bytes().to_vec(), b"data").is_ok());
+        assert!(has_odd_parity(to_u64(&cipher.generate_key())));
+    }
+}
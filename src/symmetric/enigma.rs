@@ -0,0 +1,825 @@
+use crate::analysis::fitness::{self, Quadgrams};
+use crate::cipher::{to_text, Cipher};
+use crate::error::CipherError;
+use std::fmt;
+use std::str::FromStr;
+
+/*
+    Enigma machine (army and air force model, Enigma I, plus the naval rotors VI to VIII).
+
+    Each key press sends an electric signal through:
+    plugboard -> right rotor -> middle rotor -> left rotor -> reflector -> left rotor -> middle rotor
+    -> right rotor -> plugboard, lighting up the encrypted letter.
+
+    Rotors
+    Each rotor is a fixed permutation of the alphabet (its wiring), but it turns, so the permutation
+    seen by the signal changes after every key press.
+    -> position: letter shown in the window of the machine, it changes while typing.
+    -> ring setting: rotates the wiring relative to the letters of the rotor, it doesn't change while typing.
+
+    Stepping
+    The right rotor steps on every key press (before the signal goes through), when a rotor is at its notch
+    the rotor on its left steps too, like an odometer.
+    Double stepping: when the middle rotor is at its notch it steps again together with the left rotor,
+    so the middle rotor steps on two consecutive key presses (ADU -> ADV -> AEW -> BFX).
+
+    Reflector
+    Sends the signal back through the rotors, it pairs the letters, so the machine is its own inverse
+    (the same settings encrypt and decrypt) and a letter is never encrypted to itself.
+
+    Plugboard
+    Swaps pairs of letters before and after the rotors, up to 13 pairs (10 were used in practice).
+
+    Only the 26 letters are encrypted, other symbols are passed through without stepping the rotors,
+    and the case of each letter is kept.
+*/
+
+const LETTERS: usize = 26;
+
+fn letter_index(c: char) -> Option<usize> {
+    let upper = c.to_ascii_uppercase();
+    upper
+        .is_ascii_uppercase()
+        .then(|| (upper as u8 - b'A') as usize)
+}
+
+fn index_letter(index: usize) -> char {
+    (b'A' + (index % LETTERS) as u8) as char
+}
+
+// Roman numerals are the historical names of the rotors.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotor {
+    I,
+    II,
+    III,
+    IV,
+    V,
+    VI,
+    VII,
+    VIII,
+}
+
+impl Rotor {
+    pub const ALL: [Rotor; 8] = [
+        Self::I,
+        Self::II,
+        Self::III,
+        Self::IV,
+        Self::V,
+        Self::VI,
+        Self::VII,
+        Self::VIII,
+    ];
+
+    fn wiring(&self) -> &'static str {
+        match self {
+            Self::I => "EKMFLGDQVZNTOWYHXUSPAIBRCJ",
+            Self::II => "AJDKSIRUXBLHWTMCQGZNPYFVOE",
+            Self::III => "BDFHJLCPRTXVZNYEIWGAKMUSQO",
+            Self::IV => "ESOVPZJAYQUIRHXLNFTGKDCMWB",
+            Self::V => "VZBRGITYUPSDNHLXAWMJQOFECK",
+            Self::VI => "JPGVOUMFYQBENHZRDKASXLICTW",
+            Self::VII => "NZJHGRCXMYSWBOUFAIVLPEKQDT",
+            Self::VIII => "FKQHTLXOCBJSPDZRAMEWNIUYGV",
+        }
+    }
+
+    // Window letters at which the rotor makes the rotor on its left step.
+    fn notches(&self) -> &'static str {
+        match self {
+            Self::I => "Q",
+            Self::II => "E",
+            Self::III => "V",
+            Self::IV => "J",
+            Self::V => "Z",
+            Self::VI | Self::VII | Self::VIII => "ZM",
+        }
+    }
+}
+
+impl fmt::Display for Rotor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Rotor {
+    type Err = CipherError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|rotor| rotor.to_string() == s.to_uppercase())
+            .ok_or(CipherError::InvalidKey("rotors must be between I and VIII"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Reflector {
+    #[default]
+    B,
+    C,
+}
+
+impl Reflector {
+    fn wiring(&self) -> &'static str {
+        match self {
+            Self::B => "YRUHQSLDPXNGOKMIEBFZCWVJAT",
+            Self::C => "FVPJIAOYEDRZXWGCTKUQSBNMHL",
+        }
+    }
+}
+
+impl fmt::Display for Reflector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Reflector {
+    type Err = CipherError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "B" => Ok(Self::B),
+            "C" => Ok(Self::C),
+            _ => Err(CipherError::InvalidKey("reflector must be B or C")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plugboard {
+    // letter each letter is swapped with, itself when it's not plugged
+    pairs: [usize; LETTERS],
+}
+
+impl Default for Plugboard {
+    fn default() -> Self {
+        Self {
+            pairs: std::array::from_fn(|i| i),
+        }
+    }
+}
+
+impl Plugboard {
+    fn swap(&self, letter: usize) -> usize {
+        self.pairs[letter]
+    }
+}
+
+impl fmt::Display for Plugboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pairs = (0..LETTERS)
+            .filter(|i| self.pairs[*i] > *i)
+            .map(|i| format!("{}{}", index_letter(i), index_letter(self.pairs[i])))
+            .collect::<Vec<String>>();
+        write!(f, "{}", pairs.join(" "))
+    }
+}
+
+// Pairs of letters separated by spaces or commas, like "AV BS CG".
+impl FromStr for Plugboard {
+    type Err = CipherError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut plugboard = Self::default();
+        for pair in s.split([' ', ',']).filter(|p| !p.is_empty()) {
+            let letters = pair
+                .chars()
+                .map(|c| letter_index(c).ok_or(CipherError::InvalidKeySymbol(c)))
+                .collect::<Result<Vec<usize>, CipherError>>()?;
+            let [a, b] = letters[..] else {
+                return Err(CipherError::InvalidKey(
+                    "plugboard must be pairs of letters",
+                ));
+            };
+
+            for letter in [a, b] {
+                if plugboard.pairs[letter] != letter || a == b {
+                    return Err(CipherError::DuplicatedSymbol(index_letter(letter)));
+                }
+            }
+            plugboard.pairs[a] = b;
+            plugboard.pairs[b] = a;
+        }
+
+        Ok(plugboard)
+    }
+}
+
+// Machine settings, the rotors go from left to right.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub rotors: [Rotor; 3],
+    pub reflector: Reflector,
+    pub rings: [usize; 3],
+    pub positions: [usize; 3],
+    pub plugboard: Plugboard,
+}
+
+// Any number of rotors, like "I II III IV V", "I,II,III" or "I-II-III".
+pub fn parse_rotor_list(rotors: &str) -> Result<Vec<Rotor>, CipherError> {
+    let rotors = rotors
+        .split([' ', ',', '-'])
+        .filter(|r| !r.is_empty())
+        .map(Rotor::from_str)
+        .collect::<Result<Vec<Rotor>, CipherError>>()?;
+    if (1..rotors.len()).any(|i| rotors[..i].contains(&rotors[i])) {
+        return Err(CipherError::InvalidKey("each rotor can only be used once"));
+    }
+
+    Ok(rotors)
+}
+
+fn parse_rotors(rotors: &str) -> Result<[Rotor; 3], CipherError> {
+    let rotors = parse_rotor_list(rotors)?;
+
+    rotors
+        .try_into()
+        .map_err(|_| CipherError::InvalidKey("the machine has 3 rotors"))
+}
+
+// Three letters ("BUL") or three numbers from 1 to 26 ("2 21 12").
+fn parse_letters(letters: &str) -> Result<[usize; 3], CipherError> {
+    let values = if letters.chars().any(|c| c.is_ascii_digit()) {
+        letters
+            .split([' ', ',', '-'])
+            .filter(|n| !n.is_empty())
+            .map(|n| match n.parse::<usize>() {
+                Ok(n) if (1..=LETTERS).contains(&n) => Ok(n - 1),
+                _ => Err(CipherError::InvalidKey("numbers must be between 1 and 26")),
+            })
+            .collect::<Result<Vec<usize>, CipherError>>()?
+    } else {
+        letters
+            .chars()
+            .filter(|c| ![' ', ',', '-'].contains(c))
+            .map(|c| letter_index(c).ok_or(CipherError::InvalidKeySymbol(c)))
+            .collect::<Result<Vec<usize>, CipherError>>()?
+    };
+
+    values
+        .try_into()
+        .map_err(|_| CipherError::InvalidKey("one ring and one position for each rotor"))
+}
+
+impl Settings {
+    pub fn new(
+        rotors: &str,
+        reflector: &str,
+        rings: &str,
+        positions: &str,
+        plugboard: &str,
+    ) -> Result<Self, CipherError> {
+        Ok(Self {
+            rotors: parse_rotors(rotors)?,
+            reflector: reflector.parse()?,
+            rings: parse_letters(rings)?,
+            positions: parse_letters(positions)?,
+            plugboard: plugboard.parse()?,
+        })
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            rotors: [Rotor::I, Rotor::II, Rotor::III],
+            reflector: Reflector::B,
+            rings: [0; 3],
+            positions: [0; 3],
+            plugboard: Plugboard::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Machine {
+    forward: [[usize; LETTERS]; 3],
+    backward: [[usize; LETTERS]; 3],
+    notches: [Vec<usize>; 3],
+    reflector: [usize; LETTERS],
+    rings: [usize; 3],
+    positions: [usize; 3],
+    plugboard: Plugboard,
+}
+
+fn wiring(letters: &str) -> [usize; LETTERS] {
+    let mut result = [0; LETTERS];
+    for (i, c) in letters.chars().enumerate() {
+        result[i] = letter_index(c).expect("wirings only have letters");
+    }
+    result
+}
+
+impl Machine {
+    pub fn new(settings: &Settings) -> Self {
+        let forward = settings.rotors.map(|rotor| wiring(rotor.wiring()));
+        let backward = forward.map(|wiring| {
+            let mut inverse = [0; LETTERS];
+            for (i, o) in wiring.iter().enumerate() {
+                inverse[*o] = i;
+            }
+            inverse
+        });
+
+        Self {
+            forward,
+            backward,
+            notches: settings
+                .rotors
+                .map(|rotor| rotor.notches().chars().filter_map(letter_index).collect()),
+            reflector: wiring(settings.reflector.wiring()),
+            rings: settings.rings,
+            positions: settings.positions,
+            plugboard: settings.plugboard.clone(),
+        }
+    }
+
+    // Letters shown in the windows of the machine.
+    #[allow(dead_code)]
+    pub fn positions(&self) -> String {
+        self.positions.iter().map(|p| index_letter(*p)).collect()
+    }
+
+    fn at_notch(&self, rotor: usize) -> bool {
+        self.notches[rotor].contains(&self.positions[rotor])
+    }
+
+    pub fn step(&mut self) {
+        let middle_at_notch = self.at_notch(1);
+        if middle_at_notch {
+            self.positions[0] = (self.positions[0] + 1) % LETTERS;
+        }
+        if middle_at_notch || self.at_notch(2) {
+            self.positions[1] = (self.positions[1] + 1) % LETTERS;
+        }
+        self.positions[2] = (self.positions[2] + 1) % LETTERS;
+    }
+
+    fn through(&self, shifts: &[usize; 3], rotor: usize, letter: usize, backward: bool) -> usize {
+        let wiring = if backward {
+            &self.backward
+        } else {
+            &self.forward
+        };
+        (wiring[rotor][(letter + shifts[rotor]) % LETTERS] + LETTERS - shifts[rotor]) % LETTERS
+    }
+
+    // Path of the signal through the rotors at the given positions and the reflector,
+    // without the plugboard and without stepping.
+    fn scramble(&self, positions: &[usize; 3], letter: usize) -> usize {
+        let shifts: [usize; 3] =
+            std::array::from_fn(|i| (LETTERS + positions[i] - self.rings[i]) % LETTERS);
+        let letter = (0..3)
+            .rev()
+            .fold(letter, |l, rotor| self.through(&shifts, rotor, l, false));
+        let letter = self.reflector[letter];
+        (0..3).fold(letter, |l, rotor| self.through(&shifts, rotor, l, true))
+    }
+
+    // Steps the rotors and encrypts one letter (0 = A).
+    pub fn press(&mut self, letter: usize) -> usize {
+        self.step();
+        let letter = self.scramble(&self.positions, self.plugboard.swap(letter));
+        self.plugboard.swap(letter)
+    }
+
+    pub fn encrypt(&mut self, text: &str) -> String {
+        text.chars()
+            .map(|c| match letter_index(c) {
+                Some(index) => {
+                    let encrypted = index_letter(self.press(index));
+                    if c.is_lowercase() {
+                        encrypted.to_ascii_lowercase()
+                    } else {
+                        encrypted
+                    }
+                }
+                None => c,
+            })
+            .collect()
+    }
+}
+
+// Encryption and decryption are the same operation.
+pub fn encrypt(text: &str, settings: &Settings) -> String {
+    Machine::new(settings).encrypt(text)
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Enigma;
+
+impl Cipher for Enigma {
+    type Key = Settings;
+
+    fn encrypt(&self, key: &Settings, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        Ok(encrypt(to_text(plain_text)?, key).into_bytes())
+    }
+
+    fn decrypt(&self, key: &Settings, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        Ok(encrypt(to_text(cipher_text)?, key).into_bytes())
+    }
+}
+
+/*
+    Bombe (crib based search)
+
+    A crib is a piece of plain text we expect to find in the message, like "WETTERBERICHT" (weather report).
+
+    1. A letter is never encrypted to itself, so the crib can't be at any position where one of its letters
+       matches the cipher text below it, that rules out most positions.
+
+    2. For each rotor order and starting position, the rotors (without the plugboard) are a known
+       permutation S at each letter of the crib. If the plain letter P is encrypted to C then
+       plugboard(C) = S(plugboard(P)), so guessing where one letter is plugged tells us where the others are:
+       every pair of the crib becomes a new deduction, that is the "menu" of the bombe.
+
+    3. If the deductions end up plugging a letter to two different letters, the guess is wrong.
+       When every guess of the first letter fails, the rotor order and position are wrong and the search moves on.
+       The settings that survive are "stops", along with the plugboard pairs deduced for them.
+
+    The ring settings are assumed to be AAA: changing the rings has the same effect as changing the
+    starting positions, except for the moment the middle and left rotors step, so with short cribs the
+    stop gives the positions relative to rings AAA and the rings are found afterwards by trying to decrypt.
+    Short cribs give many false stops, each extra letter of the crib removes most of them.
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stop {
+    pub rotors: [Rotor; 3],
+    pub reflector: Reflector,
+    pub positions: String,
+    pub plugboard: Plugboard,
+}
+
+impl Stop {
+    // The settings of the stop with the rings at AAA, the letters outside the menu are left unplugged.
+    pub fn settings(&self) -> Settings {
+        Settings {
+            rotors: self.rotors,
+            reflector: self.reflector,
+            rings: [0; 3],
+            positions: parse_letters(&self.positions).expect("stops have three positions"),
+            plugboard: self.plugboard.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [left, middle, right] = self.rotors;
+        write!(
+            f,
+            "rotors {} {} {}, reflector {}, positions {}, plugboard {}",
+            left, middle, right, self.reflector, self.positions, self.plugboard
+        )
+    }
+}
+
+// Positions of the cipher text where the crib may be, no letter of the crib is over the same letter.
+pub fn crib_positions(cipher_text: &str, crib: &str) -> Vec<usize> {
+    let cipher = cipher_text
+        .chars()
+        .filter_map(letter_index)
+        .collect::<Vec<usize>>();
+    let crib = crib
+        .chars()
+        .filter_map(letter_index)
+        .collect::<Vec<usize>>();
+    if crib.is_empty() || crib.len() > cipher.len() {
+        return vec![];
+    }
+
+    (0..=cipher.len() - crib.len())
+        .filter(|offset| crib.iter().zip(&cipher[*offset..]).all(|(p, c)| p != c))
+        .collect()
+}
+
+// Plugs `a` to `b` unless it contradicts what was already deduced.
+fn plug(plugboard: &mut [Option<usize>; LETTERS], a: usize, b: usize) -> bool {
+    match (plugboard[a], plugboard[b]) {
+        (Some(x), _) if x != b => false,
+        (_, Some(y)) if y != a => false,
+        _ => {
+            plugboard[a] = Some(b);
+            plugboard[b] = Some(a);
+            true
+        }
+    }
+}
+
+// Pairs of (plain, cipher) letters of the crib and the rotor positions when each one was encrypted.
+struct Menu<'a> {
+    machine: &'a Machine,
+    pairs: Vec<(usize, usize, [usize; 3])>,
+    // letters of the menu, the most connected first, guessing them finds contradictions sooner
+    letters: Vec<usize>,
+    // rotor permutations are only computed for the letters the deductions need
+    scramblers: Vec<[Option<usize>; LETTERS]>,
+}
+
+impl Menu<'_> {
+    fn scramble(&mut self, pair: usize, letter: usize) -> usize {
+        if let Some(result) = self.scramblers[pair][letter] {
+            return result;
+        }
+
+        let result = self.machine.scramble(&self.pairs[pair].2, letter);
+        self.scramblers[pair][letter] = Some(result);
+        self.scramblers[pair][result] = Some(letter);
+        result
+    }
+
+    // Follows the menu from the plugged letters until nothing new is deduced.
+    fn propagate(&mut self, plugboard: &mut [Option<usize>; LETTERS]) -> bool {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for pair in 0..self.pairs.len() {
+                let (plain, cipher, _) = self.pairs[pair];
+                let deduction = match (plugboard[plain], plugboard[cipher]) {
+                    (Some(p), None) => (cipher, self.scramble(pair, p)),
+                    (None, Some(c)) => (plain, self.scramble(pair, c)),
+                    (Some(p), Some(c)) if self.scramble(pair, p) != c => return false,
+                    _ => continue,
+                };
+                if !plug(plugboard, deduction.0, deduction.1) {
+                    return false;
+                }
+                changed = true;
+            }
+        }
+        true
+    }
+
+    // Every plugboard consistent with the menu, the letters outside the menu are left unplugged.
+    fn solve(&mut self, plugboard: [Option<usize>; LETTERS]) -> Vec<Plugboard> {
+        let Some(letter) = self
+            .letters
+            .iter()
+            .copied()
+            .find(|letter| plugboard[*letter].is_none())
+        else {
+            let mut result = Plugboard::default();
+            for (letter, other) in plugboard.iter().enumerate() {
+                result.pairs[letter] = other.unwrap_or(letter);
+            }
+            return vec![result];
+        };
+
+        let mut result = vec![];
+        for guess in 0..LETTERS {
+            let mut candidate = plugboard;
+            if plug(&mut candidate, letter, guess) && self.propagate(&mut candidate) {
+                result.extend(self.solve(candidate));
+            }
+        }
+        result
+    }
+}
+
+// Permutations of 3 different rotors out of the candidates.
+fn rotor_orders(rotors: &[Rotor]) -> Vec<[Rotor; 3]> {
+    let mut result = vec![];
+    for left in rotors {
+        for middle in rotors.iter().filter(|r| *r != left) {
+            for right in rotors.iter().filter(|r| *r != left && *r != middle) {
+                result.push([*left, *middle, *right]);
+            }
+        }
+    }
+    result
+}
+
+// Settings where the crib, starting at `offset` letters of the cipher text, could have been encrypted.
+pub fn bombe(
+    cipher_text: &str,
+    crib: &str,
+    offset: usize,
+    rotors: &[Rotor],
+    reflector: Reflector,
+) -> Vec<Stop> {
+    let cipher = cipher_text
+        .chars()
+        .filter_map(letter_index)
+        .collect::<Vec<usize>>();
+    let crib = crib
+        .chars()
+        .filter_map(letter_index)
+        .collect::<Vec<usize>>();
+    if crib.is_empty() || offset + crib.len() > cipher.len() {
+        return vec![];
+    }
+
+    let mut connections = [0; LETTERS];
+    for (plain, encrypted) in crib.iter().zip(&cipher[offset..]) {
+        connections[*plain] += 1;
+        connections[*encrypted] += 1;
+    }
+    let mut letters = (0..LETTERS)
+        .filter(|l| connections[*l] > 0)
+        .collect::<Vec<usize>>();
+    letters.sort_by_key(|l| std::cmp::Reverse(connections[*l]));
+
+    let mut stops = vec![];
+    for order in rotor_orders(rotors) {
+        let mut machine = Machine::new(&Settings {
+            rotors: order,
+            reflector,
+            ..Settings::default()
+        });
+
+        for start in 0..LETTERS * LETTERS * LETTERS {
+            let positions = [
+                start / (LETTERS * LETTERS),
+                start / LETTERS % LETTERS,
+                start % LETTERS,
+            ];
+            machine.positions = positions;
+            for _ in 0..offset {
+                machine.step();
+            }
+
+            let pairs = crib
+                .iter()
+                .zip(&cipher[offset..])
+                .map(|(plain, encrypted)| {
+                    machine.step();
+                    (*plain, *encrypted, machine.positions)
+                })
+                .collect::<Vec<(usize, usize, [usize; 3])>>();
+
+            let mut menu = Menu {
+                machine: &machine,
+                letters: letters.clone(),
+                scramblers: vec![[None; LETTERS]; pairs.len()],
+                pairs,
+            };
+            for plugboard in menu.solve([None; LETTERS]) {
+                stops.push(Stop {
+                    rotors: order,
+                    reflector,
+                    positions: positions.iter().map(|p| index_letter(*p)).collect(),
+                    plugboard,
+                });
+            }
+        }
+    }
+
+    stops
+}
+
+// Runs the bombe with the crib at `offset`, or at every position it may be, every order of 3 of the rotors is tried.
+// The stops are ranked by how much the message decrypted with them looks like english.
+pub fn crack(
+    cipher_text: &str,
+    crib: &str,
+    offset: Option<usize>,
+    rotors: &[Rotor],
+    reflector: Reflector,
+) -> Vec<(Stop, f64, String)> {
+    let offsets = offset.map_or_else(|| crib_positions(cipher_text, crib), |offset| vec![offset]);
+    let candidates = offsets
+        .into_iter()
+        .flat_map(|offset| bombe(cipher_text, crib, offset, rotors, reflector))
+        .map(|stop| {
+            let plain_text = encrypt(cipher_text, &stop.settings());
+            (stop, plain_text)
+        });
+    fitness::rank(candidates, Quadgrams::english())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_message() {
+        let settings = Settings::default();
+        assert_eq!(encrypt("AAAAA", &settings), "BDZGO");
+        assert_eq!(encrypt("BDZGO", &settings), "AAAAA");
+        assert_eq!(encrypt("Hello, World!", &settings), "Ilbda, Amtaz!");
+
+        // a letter is never encrypted to itself
+        let encrypted = encrypt(&"A".repeat(1000), &settings);
+        assert!(!encrypted.contains('A'));
+    }
+
+    #[test]
+    fn double_stepping() {
+        let settings = Settings::new("I II III", "B", "AAA", "ADU", "").unwrap();
+        let mut machine = Machine::new(&settings);
+        let mut positions = vec![];
+        for _ in 0..4 {
+            machine.step();
+            positions.push(machine.positions());
+        }
+        assert_eq!(positions, vec!["ADV", "AEW", "BFX", "BFY"]);
+    }
+
+    #[test]
+    fn operation_barbarossa() {
+        // message sent on 7 July 1941, the message key BLA was decrypted from the indicator
+        let settings = Settings::new(
+            "II IV V",
+            "B",
+            "2 21 12",
+            "BLA",
+            "AV BS CG DL FU HZ IN KM OW RX",
+        )
+        .unwrap();
+        let cipher_text =
+            "EDPUD NRGYS ZRCXN UYTPO MRMBO FKTBZ REZKM LXLVE FGUEY SIOZV EQMIK UBPMM \
+            YLKLT TDEIS MDICA GYKUA CTCDO MOHWX MUUIA UBSTS LRNBZ SZWNR FXWFY SSXJZ VIJHI DISHP \
+            RKLKA YUPAD TXQSP INQMA TLPIF SVKDA SCTAC DPBOP VHJK";
+        let plain_text = encrypt(cipher_text, &settings).replace(' ', "");
+        assert_eq!(
+            plain_text,
+            "AUFKLXABTEILUNGXVONXKURTINOWAXKURTINOWAXNORDWESTLXSEBEZXSEBEZXUAFFLIEGERSTRASZERIQTUNGX\
+            DUBROWKIXDUBROWKIXOPOTSCHKAXOPOTSCHKAXUMXEINSAQTDREINULLXUHRANGETRETENXANGRIFFXINFXRGTX"
+        );
+    }
+
+    #[test]
+    fn invalid_settings() {
+        assert_eq!(
+            Settings::new("I II", "B", "AAA", "AAA", ""),
+            Err(CipherError::InvalidKey("the machine has 3 rotors"))
+        );
+        assert_eq!(
+            Settings::new("I I III", "B", "AAA", "AAA", ""),
+            Err(CipherError::InvalidKey("each rotor can only be used once"))
+        );
+        assert_eq!(
+            Settings::new("I II IX", "B", "AAA", "AAA", ""),
+            Err(CipherError::InvalidKey("rotors must be between I and VIII"))
+        );
+        assert_eq!(
+            Settings::new("I II III", "A", "AAA", "AAA", ""),
+            Err(CipherError::InvalidKey("reflector must be B or C"))
+        );
+        assert_eq!(
+            Settings::new("I II III", "B", "0 1 2", "AAA", ""),
+            Err(CipherError::InvalidKey("numbers must be between 1 and 26"))
+        );
+        assert_eq!(
+            Settings::new("I II III", "B", "AAA", "AA", ""),
+            Err(CipherError::InvalidKey(
+                "one ring and one position for each rotor"
+            ))
+        );
+        assert_eq!(
+            Settings::new("I II III", "B", "AAA", "AAA", "AB AC"),
+            Err(CipherError::DuplicatedSymbol('A'))
+        );
+
+        let plugboard = "AV BS CG".parse::<Plugboard>().unwrap();
+        assert_eq!(plugboard.to_string(), "AV BS CG");
+    }
+
+    #[test]
+    fn crib_search() {
+        let settings = Settings::new("V III II", "B", "AAA", "KDO", "AE BT CL QW RZ").unwrap();
+        let cipher_text = encrypt("WETTERBERICHTXNORDSEEXREGENXWIND", &settings);
+
+        assert!(crib_positions(&cipher_text, "WETTERBERICHT").contains(&0));
+        assert!(crib_positions("ABC", "A").iter().all(|p| *p != 0));
+
+        let stops = bombe(
+            &cipher_text,
+            "WETTERBERICHT",
+            0,
+            &settings.rotors,
+            Reflector::B,
+        );
+        let stop = stops
+            .iter()
+            .find(|stop| stop.rotors == settings.rotors && stop.positions == "KDO")
+            .expect("the right settings are a stop");
+
+        // the deduced plugboard decrypts the message
+        assert!(encrypt(&cipher_text, &stop.settings()).starts_with("WETTERBERICHT"));
+        assert!(stops.len() < 10);
+
+        // the right stop decrypts to the most english looking text
+        let candidates = crack(
+            &cipher_text,
+            "WETTERBERICHT",
+            Some(0),
+            &settings.rotors,
+            Reflector::B,
+        );
+        assert_eq!(candidates[0].0.positions, "KDO");
+        assert_eq!(
+            candidates[0].0.to_string(),
+            format!(
+                "rotors V III II, reflector B, positions KDO, plugboard {}",
+                candidates[0].0.plugboard
+            )
+        );
+    }
+}
@@ -0,0 +1,437 @@
+use crate::cipher::{to_text, Cipher};
+use crate::error::CipherError;
+use crate::symmetric::{transposition_cipher, ALPHABET};
+
+/*
+    Fractionating ciphers split each symbol in smaller pieces (its coordinates in a Polybius square),
+    move those pieces around and join them back into symbols, so every symbol of the cipher text
+    depends on more than one symbol of the plain text. It combines a substitution (the square)
+    with a transposition (moving the coordinates).
+
+    Squares
+    Like in playfair, the keyword without repeated symbols is followed by the rest of the symbols.
+    -> ADFGVX: 6x6 square with letters and digits.
+    -> Bifid: 5x5 square with letters, J is merged with I.
+    -> Trifid: 3x3x3 cube with the 27 symbols of the alphabet (space plus A to Z).
+    Symbols of the text that aren't part of the square are removed, lowercase letters are uppercased.
+
+    ADFGVX
+    Each symbol is replaced by the labels of its row and column (A, D, F, G, V or X),
+    then the result goes through a columnar transposition with a second keyword.
+
+      A D F G V X
+    A N A 1 C 3 H
+    D 8 T B 2 O M         A -> AD, T -> DD ...
+    F E 5 W R P D
+    G 4 F 6 G 7 I         ATTACK AT 1200AM -> ADDDDDADAGVGADDD... -> transposition (PRIVACY)
+    V 9 J 0 K L Q
+    X S U V X Y Z
+
+    Bifid and Trifid
+    The text is split in blocks of `period` symbols (0 for the whole text), the coordinates of the symbols
+    of a block are written one below the other and read row by row, every 2 (bifid) or 3 (trifid)
+    coordinates read become a symbol again.
+
+    square: BGWKZ QPNDS IOAXE FCLUM THYVR, plain text: FLEEATONCE, period: 0
+
+             F L E E A T O N C E
+    rows:    4 4 3 3 3 5 3 2 4 3
+    columns: 1 3 5 5 3 1 2 3 2 5
+
+    read: 44 33 35 32 43 13 55 31 23 25 -> UAEOLWRINS
+*/
+
+const ADFGVX_LABELS: [char; 6] = ['A', 'D', 'F', 'G', 'V', 'X'];
+const DEFAULT_PERIOD: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Square {
+    symbols: Vec<char>,
+    side: usize,
+    dimensions: usize,
+    // Bifid merges J with I to fit 26 letters in 25 cells.
+    merge_j: bool,
+}
+
+impl Square {
+    fn new(
+        keyword: &str,
+        symbols: &[char],
+        side: usize,
+        dimensions: usize,
+        merge_j: bool,
+    ) -> Result<Self, CipherError> {
+        let mut square = Self {
+            symbols: Vec::with_capacity(symbols.len()),
+            side,
+            dimensions,
+            merge_j,
+        };
+
+        // spaces separate the words of the keyword, unless the space is a symbol of the square
+        for c in keyword.chars().filter(|c| *c != ' ' || symbols.contains(c)) {
+            let symbol = square
+                .normalize(c)
+                .filter(|s| symbols.contains(s))
+                .ok_or(CipherError::InvalidKeySymbol(c))?;
+            if !square.symbols.contains(&symbol) {
+                square.symbols.push(symbol);
+            }
+        }
+
+        if square.symbols.is_empty() {
+            return Err(CipherError::InvalidKey("secret must not be empty"));
+        }
+
+        for symbol in symbols {
+            if !square.symbols.contains(symbol) {
+                square.symbols.push(*symbol);
+            }
+        }
+
+        Ok(square)
+    }
+
+    pub fn adfgvx(keyword: &str) -> Result<Self, CipherError> {
+        let symbols = ('A'..='Z').chain('0'..='9').collect::<Vec<char>>();
+        Self::new(keyword, &symbols, 6, 2, false)
+    }
+
+    pub fn bifid(keyword: &str) -> Result<Self, CipherError> {
+        let symbols = ('A'..='Z').filter(|c| *c != 'J').collect::<Vec<char>>();
+        Self::new(keyword, &symbols, 5, 2, true)
+    }
+
+    pub fn trifid(keyword: &str) -> Result<Self, CipherError> {
+        Self::new(keyword, &ALPHABET, 3, 3, false)
+    }
+
+    fn normalize(&self, c: char) -> Option<char> {
+        match c.to_ascii_uppercase() {
+            'J' if self.merge_j => Some('I'),
+            c => Some(c),
+        }
+    }
+
+    // Coordinates of a symbol from the outermost to the innermost (row then column for a square).
+    fn coordinates(&self, c: char) -> Option<Vec<usize>> {
+        let symbol = self.normalize(c)?;
+        let mut index = self.symbols.iter().position(|s| *s == symbol)?;
+        let mut coordinates = vec![0; self.dimensions];
+        for coordinate in coordinates.iter_mut().rev() {
+            *coordinate = index % self.side;
+            index /= self.side;
+        }
+        Some(coordinates)
+    }
+
+    fn symbol(&self, coordinates: &[usize]) -> char {
+        let index = coordinates.iter().fold(0, |index, c| index * self.side + c);
+        self.symbols[index]
+    }
+
+    #[allow(dead_code)]
+    pub fn rows(&self) -> Vec<String> {
+        self.symbols
+            .chunks(self.side)
+            .map(|row| row.iter().collect())
+            .collect()
+    }
+
+    // Symbols of the text that are part of the square, as coordinates.
+    fn text_coordinates(&self, text: &str) -> Vec<Vec<usize>> {
+        text.chars().filter_map(|c| self.coordinates(c)).collect()
+    }
+}
+
+fn check_period(period: usize, length: usize) -> usize {
+    if period == 0 {
+        length.max(1)
+    } else {
+        period
+    }
+}
+
+// The coordinates of a block are written as rows (first coordinates, second coordinates...)
+// and read again in groups of `dimensions`.
+fn fractionate(square: &Square, text: &str, period: usize) -> String {
+    let coordinates = square.text_coordinates(text);
+    let period = check_period(period, coordinates.len());
+
+    coordinates
+        .chunks(period)
+        .flat_map(|block| {
+            let rows = (0..square.dimensions)
+                .flat_map(|dimension| block.iter().map(move |c| c[dimension]))
+                .collect::<Vec<usize>>();
+            rows.chunks(square.dimensions)
+                .map(|c| square.symbol(c))
+                .collect::<Vec<char>>()
+        })
+        .collect()
+}
+
+fn defractionate(square: &Square, text: &str, period: usize) -> String {
+    let coordinates = square.text_coordinates(text);
+    let period = check_period(period, coordinates.len());
+
+    coordinates
+        .chunks(period)
+        .flat_map(|block| {
+            let flat = block.concat();
+            (0..block.len())
+                .map(|i| {
+                    let symbol = (0..square.dimensions)
+                        .map(|dimension| flat[dimension * block.len() + i])
+                        .collect::<Vec<usize>>();
+                    square.symbol(&symbol)
+                })
+                .collect::<Vec<char>>()
+        })
+        .collect()
+}
+
+pub fn bifid_encrypt(plain_text: &str, secret: &str, period: usize) -> Result<String, CipherError> {
+    Ok(fractionate(&Square::bifid(secret)?, plain_text, period))
+}
+
+pub fn bifid_decrypt(
+    cipher_text: &str,
+    secret: &str,
+    period: usize,
+) -> Result<String, CipherError> {
+    Ok(defractionate(&Square::bifid(secret)?, cipher_text, period))
+}
+
+pub fn trifid_encrypt(
+    plain_text: &str,
+    secret: &str,
+    period: usize,
+) -> Result<String, CipherError> {
+    Ok(fractionate(&Square::trifid(secret)?, plain_text, period))
+}
+
+pub fn trifid_decrypt(
+    cipher_text: &str,
+    secret: &str,
+    period: usize,
+) -> Result<String, CipherError> {
+    Ok(defractionate(&Square::trifid(secret)?, cipher_text, period))
+}
+
+pub fn adfgvx_encrypt(
+    plain_text: &str,
+    square_secret: &str,
+    transposition_secret: &str,
+) -> Result<String, CipherError> {
+    let square = Square::adfgvx(square_secret)?;
+    let labels = square
+        .text_coordinates(plain_text)
+        .into_iter()
+        .flat_map(|c| [ADFGVX_LABELS[c[0]], ADFGVX_LABELS[c[1]]])
+        .collect::<String>();
+    transposition_cipher::columnar_encrypt(&labels, transposition_secret)
+}
+
+// Only the labels are accepted in the cipher text, spaces between groups are ignored.
+pub fn adfgvx_decrypt(
+    cipher_text: &str,
+    square_secret: &str,
+    transposition_secret: &str,
+) -> Result<String, CipherError> {
+    let square = Square::adfgvx(square_secret)?;
+    let labels = cipher_text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            ADFGVX_LABELS
+                .iter()
+                .position(|l| *l == c.to_ascii_uppercase())
+                .ok_or(CipherError::InvalidInputSymbol(c))
+        })
+        .collect::<Result<Vec<usize>, CipherError>>()?;
+    if !labels.len().is_multiple_of(2) {
+        return Err(CipherError::InvalidBlock);
+    }
+
+    let labels = labels
+        .into_iter()
+        .map(|l| ADFGVX_LABELS[l])
+        .collect::<String>();
+    let labels = transposition_cipher::columnar_decrypt(&labels, transposition_secret)?
+        .chars()
+        .map(|c| {
+            ADFGVX_LABELS
+                .iter()
+                .position(|l| *l == c)
+                .unwrap_or_default()
+        })
+        .collect::<Vec<usize>>();
+
+    Ok(labels.chunks(2).map(|c| square.symbol(c)).collect())
+}
+
+// The key is (square secret, transposition secret).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Adfgvx;
+
+impl Cipher for Adfgvx {
+    type Key = (String, String);
+
+    fn encrypt(&self, key: &(String, String), plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        adfgvx_encrypt(to_text(plain_text)?, &key.0, &key.1).map(String::into_bytes)
+    }
+
+    fn decrypt(&self, key: &(String, String), cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        adfgvx_decrypt(to_text(cipher_text)?, &key.0, &key.1).map(String::into_bytes)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Bifid {
+    pub period: usize,
+}
+
+impl Bifid {
+    pub fn new(period: usize) -> Self {
+        Self { period }
+    }
+}
+
+impl Default for Bifid {
+    fn default() -> Self {
+        Self::new(DEFAULT_PERIOD)
+    }
+}
+
+impl Cipher for Bifid {
+    type Key = String;
+
+    fn encrypt(&self, key: &String, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        bifid_encrypt(to_text(plain_text)?, key, self.period).map(String::into_bytes)
+    }
+
+    fn decrypt(&self, key: &String, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        bifid_decrypt(to_text(cipher_text)?, key, self.period).map(String::into_bytes)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Trifid {
+    pub period: usize,
+}
+
+impl Trifid {
+    pub fn new(period: usize) -> Self {
+        Self { period }
+    }
+}
+
+impl Default for Trifid {
+    fn default() -> Self {
+        Self::new(DEFAULT_PERIOD)
+    }
+}
+
+impl Cipher for Trifid {
+    type Key = String;
+
+    fn encrypt(&self, key: &String, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        trifid_encrypt(to_text(plain_text)?, key, self.period).map(String::into_bytes)
+    }
+
+    fn decrypt(&self, key: &String, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        trifid_decrypt(to_text(cipher_text)?, key, self.period).map(String::into_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squares() {
+        let square = Square::adfgvx("NA1C3H8TB2OME5WRPD4F6G7I9J0KLQSUVXYZ").unwrap();
+        assert_eq!(
+            square.rows(),
+            vec!["NA1C3H", "8TB2OM", "E5WRPD", "4F6G7I", "9J0KLQ", "SUVXYZ"]
+        );
+        assert_eq!(square.coordinates('A'), Some(vec![0, 1]));
+
+        let square = Square::bifid("Playfair Example").unwrap();
+        assert_eq!(
+            square.rows(),
+            vec!["PLAYF", "IREXM", "BCDGH", "KNOQS", "TUVWZ"]
+        );
+        assert_eq!(square.coordinates('j'), square.coordinates('I'));
+
+        let cube = Square::trifid("FELIX MARIE DELASTELLE").unwrap();
+        assert_eq!(cube.coordinates('F'), Some(vec![0, 0, 0]));
+        assert_eq!(cube.coordinates(' '), Some(vec![0, 1, 2]));
+
+        assert_eq!(
+            Square::bifid(""),
+            Err(CipherError::InvalidKey("secret must not be empty"))
+        );
+        assert_eq!(
+            Square::bifid("SECRET1"),
+            Err(CipherError::InvalidKeySymbol('1'))
+        );
+    }
+
+    #[test]
+    fn adfgvx() {
+        let square = "NA1C3H8TB2OME5WRPD4F6G7I9J0KLQSUVXYZ";
+        let result = adfgvx_encrypt("ATTACK AT 1200AM", square, "PRIVACY").unwrap();
+        assert_eq!(result, "DGDDDAGDDGAFADDFDADVDVFAADVX");
+        assert_eq!(
+            adfgvx_decrypt("DGDDD AGDDG AFADD FDADV DVFAA DVX", square, "PRIVACY").unwrap(),
+            "ATTACKAT1200AM"
+        );
+
+        assert_eq!(
+            adfgvx_decrypt("DGDDB", square, "PRIVACY"),
+            Err(CipherError::InvalidInputSymbol('B'))
+        );
+        assert_eq!(
+            adfgvx_decrypt("DGD", square, "PRIVACY"),
+            Err(CipherError::InvalidBlock)
+        );
+    }
+
+    #[test]
+    fn bifid() {
+        let secret = "BGWKZQPNDSIOAXEFCLUMTHYVR";
+        let result = bifid_encrypt("Flee at once", secret, 0).unwrap();
+        assert_eq!(result, "UAEOLWRINS");
+        assert_eq!(bifid_decrypt(&result, secret, 0).unwrap(), "FLEEATONCE");
+
+        let cipher = Bifid::default();
+        let secret = "PLAYFAIR EXAMPLE".to_string();
+        let encrypted = cipher.encrypt(&secret, b"DEFEND THE EAST WALL").unwrap();
+        assert_eq!(
+            cipher.decrypt(&secret, &encrypted).unwrap(),
+            b"DEFENDTHEEASTWALL"
+        );
+    }
+
+    #[test]
+    fn trifid() {
+        let secret = "FELIXMARDSTBCGHJKNOPQUVWYZ ";
+        let result = trifid_encrypt("AIDETOILECIELTAIDERA", secret, 5).unwrap();
+        assert_eq!(result, "FMJFVOISSUFTFPUFEQQC");
+        assert_eq!(
+            trifid_decrypt(&result, secret, 5).unwrap(),
+            "AIDETOILECIELTAIDERA"
+        );
+
+        // spaces are part of the cube, so they are kept
+        let cipher = Trifid::new(7);
+        let secret = "FELIX MARIE DELASTELLE".to_string();
+        let encrypted = cipher.encrypt(&secret, b"Attack at dawn!").unwrap();
+        assert_eq!(
+            cipher.decrypt(&secret, &encrypted).unwrap(),
+            b"ATTACK AT DAWN"
+        );
+    }
+}
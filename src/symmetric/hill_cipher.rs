@@ -0,0 +1,413 @@
+use crate::asymmetric::rsa::{euclidean_gcd, modular_inverse};
+use crate::cipher::{to_text, Cipher, KeyGen};
+use crate::error::CipherError;
+use crate::symmetric::alphabet::Alphabet;
+use crate::symmetric::ALPHABET;
+use rand::Rng;
+
+/*
+    Hill cipher encrypts blocks of N symbols at once by multiplying them by an NxN key matrix mod 27.
+
+    The secret is a word with N * N symbols written row by row in the matrix,
+    each symbol being its position in `ALPHABET` (space = 0, A = 1, ..., Z = 26).
+
+    secret: "HELP" (N = 2)      plain text block: "HI"
+
+    | 8  5  |   | 8 |   | 8 * 8 + 5 * 9   |   | 109 |          | 1  |   | A |
+    | 12 16 | * | 9 | = | 12 * 8 + 16 * 9 | = | 240 | mod 27 = | 24 | = | X |
+
+    encryption
+    C = K * P mod 27
+    decryption
+    P = K.pow(-1) * C mod 27
+
+    Inverse matrix
+    K.pow(-1) = det(K).pow(-1) * adj(K) mod 27, where adj(K) is the transposed matrix of cofactors.
+    It only exists when the determinant has a modular inverse, gcd(det(K), 27) == 1,
+    otherwise two different blocks would be encrypted to the same one.
+
+    Symbols outside of the alphabet are left untouched like in the other classical ciphers,
+    and when the last block is incomplete it is padded with spaces, so the decrypted text
+    may have up to N - 1 spaces at the end.
+
+    Unlike caesar or vigenere, changing one symbol of a block changes every symbol of the encrypted block,
+    so single symbol frequencies are hidden, but the cipher is linear and falls to a known plain text attack.
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Matrix {
+    size: usize,
+    // row by row, every value mod 27
+    values: Vec<i64>,
+}
+
+fn modulus() -> i64 {
+    ALPHABET.len() as i64
+}
+
+impl Matrix {
+    // Columns are the blocks, the first block being the first column.
+    fn from_columns(columns: &[&[usize]]) -> Self {
+        let size = columns.len();
+        let mut values = vec![0; size * size];
+        for (column, block) in columns.iter().enumerate() {
+            for (row, value) in block.iter().enumerate() {
+                values[row * size + column] = *value as i64;
+            }
+        }
+        Self { size, values }
+    }
+
+    fn get(&self, row: usize, column: usize) -> i64 {
+        self.values[row * self.size + column]
+    }
+
+    // Matrix without the given row and column.
+    fn minor(&self, row: usize, column: usize) -> Self {
+        let values = (0..self.size)
+            .filter(|r| *r != row)
+            .flat_map(|r| (0..self.size).filter(|c| *c != column).map(move |c| (r, c)))
+            .map(|(r, c)| self.get(r, c))
+            .collect();
+        Self {
+            size: self.size - 1,
+            values,
+        }
+    }
+
+    // Laplace expansion along the first row, good enough for the small keys of the cipher.
+    fn determinant(&self) -> i64 {
+        match self.size {
+            0 => 1,
+            1 => self.values[0].rem_euclid(modulus()),
+            _ => (0..self.size)
+                .map(|column| {
+                    let sign = if column % 2 == 0 { 1 } else { -1 };
+                    sign * self.get(0, column) * self.minor(0, column).determinant()
+                })
+                .sum::<i64>()
+                .rem_euclid(modulus()),
+        }
+    }
+
+    fn inverse(&self) -> Result<Self, CipherError> {
+        let determinant = self.determinant();
+        if euclidean_gcd(determinant as u128, modulus() as u128) != 1 {
+            return Err(CipherError::InvalidKey(
+                "the determinant of the key matrix must be coprime with 27",
+            ));
+        }
+
+        let determinant_inverse = modular_inverse(determinant as i128, modulus() as i128).1 as i64;
+        let mut values = vec![0; self.size * self.size];
+        for row in 0..self.size {
+            for column in 0..self.size {
+                let sign = if (row + column) % 2 == 0 { 1 } else { -1 };
+                let cofactor = sign * self.minor(row, column).determinant();
+                // the adjugate is transposed
+                values[column * self.size + row] =
+                    (determinant_inverse * cofactor).rem_euclid(modulus());
+            }
+        }
+
+        Ok(Self {
+            size: self.size,
+            values,
+        })
+    }
+
+    fn multiply(&self, other: &Self) -> Self {
+        let mut values = vec![0; self.size * self.size];
+        for row in 0..self.size {
+            for column in 0..self.size {
+                values[row * self.size + column] = (0..self.size)
+                    .map(|i| self.get(row, i) * other.get(i, column))
+                    .sum::<i64>()
+                    .rem_euclid(modulus());
+            }
+        }
+
+        Self {
+            size: self.size,
+            values,
+        }
+    }
+
+    fn transform(&self, block: &[usize]) -> Vec<usize> {
+        (0..self.size)
+            .map(|row| {
+                (0..self.size)
+                    .map(|column| self.get(row, column) * block[column] as i64)
+                    .sum::<i64>()
+                    .rem_euclid(modulus()) as usize
+            })
+            .collect()
+    }
+}
+
+fn key_matrix(secret: &str) -> Result<Matrix, CipherError> {
+    let alphabet = Alphabet::default();
+    let values = secret
+        .chars()
+        .map(|c| {
+            alphabet
+                .position(c)
+                .map(|p| p as i64)
+                .ok_or(CipherError::InvalidKeySymbol(c))
+        })
+        .collect::<Result<Vec<i64>, CipherError>>()?;
+
+    let size = (values.len() as f64).sqrt() as usize;
+    if values.is_empty() || size * size != values.len() {
+        return Err(CipherError::InvalidKey(
+            "secret length must be a perfect square",
+        ));
+    }
+
+    Ok(Matrix { size, values })
+}
+
+fn from_matrix(matrix: &Matrix) -> String {
+    matrix
+        .values
+        .iter()
+        .map(|v| ALPHABET[*v as usize])
+        .collect()
+}
+
+// Applies the matrix to the blocks of known symbols, keeping the other symbols in place.
+fn transform(text: &str, matrix: &Matrix) -> String {
+    let alphabet = Alphabet::default();
+    let mut symbols = text
+        .chars()
+        .filter_map(|c| alphabet.position(c))
+        .collect::<Vec<usize>>();
+    let padding = (matrix.size - symbols.len() % matrix.size) % matrix.size;
+    symbols.resize(symbols.len() + padding, 0);
+
+    let mut transformed = symbols
+        .chunks(matrix.size)
+        .flat_map(|block| matrix.transform(block))
+        .map(|s| ALPHABET[s]);
+
+    let mut result = text
+        .chars()
+        .map(|c| match alphabet.position(c) {
+            Some(_) => transformed
+                .next()
+                .expect("one symbol for each known symbol"),
+            None => c,
+        })
+        .collect::<String>();
+    result.extend(transformed);
+    result
+}
+
+pub fn encrypt(plain_text: &str, secret: &str) -> Result<String, CipherError> {
+    let matrix = key_matrix(secret)?;
+    matrix.inverse()?;
+    Ok(transform(plain_text, &matrix))
+}
+
+pub fn decrypt(cipher_text: &str, secret: &str) -> Result<String, CipherError> {
+    let matrix = key_matrix(secret)?.inverse()?;
+    Ok(transform(cipher_text, &matrix))
+}
+
+// `size` is only used to generate keys, encryption takes the size from the secret.
+#[derive(Debug, Clone)]
+pub struct HillCipher {
+    pub size: usize,
+}
+
+impl HillCipher {
+    pub fn new(size: usize) -> Self {
+        Self { size }
+    }
+}
+
+impl Default for HillCipher {
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
+
+impl Cipher for HillCipher {
+    type Key = String;
+
+    fn encrypt(&self, key: &String, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        encrypt(to_text(plain_text)?, key).map(String::into_bytes)
+    }
+
+    fn decrypt(&self, key: &String, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        decrypt(to_text(cipher_text)?, key).map(String::into_bytes)
+    }
+}
+
+impl KeyGen for HillCipher {
+    // Random matrices until one of them can be inverted, around 2 out of 3 can.
+    fn generate_key(&self) -> String {
+        let mut rng = rand::thread_rng();
+        loop {
+            let matrix = Matrix {
+                size: self.size,
+                values: (0..self.size * self.size)
+                    .map(|_| rng.gen_range(0..modulus()))
+                    .collect(),
+            };
+            if matrix.inverse().is_ok() {
+                return from_matrix(&matrix);
+            }
+        }
+    }
+}
+
+/*
+    Known plain text attack
+
+    Writing N plain text blocks as the columns of a matrix P and their encrypted blocks as the columns of C:
+
+    C = K * P mod 27, so K = C * P.pow(-1) mod 27
+
+    P must be invertible, so we look for N blocks (at the same positions in both texts)
+    whose matrix has a determinant coprime with 27, the more known text, the more chances to find them.
+*/
+
+// Indexes of the next combination of `n` blocks out of `total`, in lexicographic order.
+fn next_combination(indexes: &mut [usize], total: usize) -> bool {
+    let n = indexes.len();
+    for i in (0..n).rev() {
+        if indexes[i] < total - n + i {
+            indexes[i] += 1;
+            for j in i + 1..n {
+                indexes[j] = indexes[j - 1] + 1;
+            }
+            return true;
+        }
+    }
+    false
+}
+
+// Recovers the secret of size `size` from a plain text and its cipher text.
+pub fn known_plain_text_attack(
+    plain_text: &str,
+    cipher_text: &str,
+    size: usize,
+) -> Result<String, CipherError> {
+    let alphabet = Alphabet::default();
+    let plain = crate::analysis::symbols(plain_text, &alphabet);
+    let cipher = crate::analysis::symbols(cipher_text, &alphabet);
+    if size == 0 || plain.len() > cipher.len() {
+        return Err(CipherError::InvalidBlock);
+    }
+
+    let plain_blocks = plain.chunks_exact(size).collect::<Vec<&[usize]>>();
+    let cipher_blocks = cipher.chunks_exact(size).collect::<Vec<&[usize]>>();
+    if plain_blocks.len() < size {
+        return Err(CipherError::InvalidKey(
+            "at least one known block for each row of the key is needed",
+        ));
+    }
+
+    let mut indexes = (0..size).collect::<Vec<usize>>();
+    loop {
+        let p = Matrix::from_columns(&indexes.iter().map(|i| plain_blocks[*i]).collect::<Vec<_>>());
+        if let Ok(p_inverse) = p.inverse() {
+            let c = Matrix::from_columns(
+                &indexes
+                    .iter()
+                    .map(|i| cipher_blocks[*i])
+                    .collect::<Vec<_>>(),
+            );
+            return Ok(from_matrix(&c.multiply(&p_inverse)));
+        }
+
+        if !next_combination(&mut indexes, plain_blocks.len()) {
+            return Err(CipherError::InvalidKey(
+                "the known blocks don't identify a single secret",
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_message() {
+        let result = encrypt("HI", "HELP").unwrap();
+        assert_eq!(result, "AX");
+        assert_eq!(decrypt(&result, "HELP").unwrap(), "HI");
+
+        // unknown symbols are kept in place and the last block is padded with spaces
+        let result = encrypt("Hello, World!", "ALPHABETS").unwrap();
+        assert_eq!(result.chars().count(), 14);
+        assert_eq!(result.chars().nth(5), Some(','));
+        assert_eq!(decrypt(&result, "ALPHABETS").unwrap(), "HELLO, WORLD! ");
+
+        // determinant of "ABBA" is 1 * 1 - 2 * 2 = -3, not coprime with 27
+        assert_eq!(
+            encrypt("HELLO", "ABBA"),
+            Err(CipherError::InvalidKey(
+                "the determinant of the key matrix must be coprime with 27"
+            ))
+        );
+        assert_eq!(
+            encrypt("HELLO", "ABC"),
+            Err(CipherError::InvalidKey(
+                "secret length must be a perfect square"
+            ))
+        );
+        assert_eq!(
+            encrypt("HELLO", "AB!D"),
+            Err(CipherError::InvalidKeySymbol('!'))
+        );
+    }
+
+    #[test]
+    fn inverse_matrix() {
+        for secret in ["HELP", "ALPHABETS", "CRYPTOGRAPHY ABD"] {
+            let matrix = key_matrix(secret).unwrap();
+            let inverse = matrix.inverse().unwrap();
+            let identity = matrix.multiply(&inverse);
+            for row in 0..matrix.size {
+                for column in 0..matrix.size {
+                    assert_eq!(identity.get(row, column), (row == column) as i64);
+                }
+            }
+        }
+        // the determinant is a multiple of 3, so it has no inverse mod 27
+        assert!(key_matrix("CRYPTOGRAPHY ABC").unwrap().inverse().is_err());
+
+        let cipher = HillCipher::new(4);
+        let secret = cipher.generate_key();
+        assert_eq!(secret.len(), 16);
+        let encrypted = cipher.encrypt(&secret, b"ATTACK AT DAWN").unwrap();
+        assert_eq!(
+            cipher.decrypt(&secret, &encrypted).unwrap(),
+            b"ATTACK AT DAWN  "
+        );
+    }
+
+    #[test]
+    fn known_plain_text() {
+        let plain_text = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG";
+        for size in [2, 3] {
+            let secret = HillCipher::new(size).generate_key();
+            let cipher_text = encrypt(plain_text, &secret).unwrap();
+            assert_eq!(
+                known_plain_text_attack(plain_text, &cipher_text, size),
+                Ok(secret)
+            );
+        }
+
+        assert!(known_plain_text_attack("AB", "CD", 2).is_err());
+        assert_eq!(
+            known_plain_text_attack("AAAAAAAA", &encrypt("AAAAAAAA", "HELP").unwrap(), 2),
+            Err(CipherError::InvalidKey(
+                "the known blocks don't identify a single secret"
+            ))
+        );
+    }
+}
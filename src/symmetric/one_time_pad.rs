@@ -1,8 +1,18 @@
 #![allow(dead_code)]
 
+use crate::analysis::fitness::{Bigrams, Quadgrams};
+use crate::analysis::Language;
+use crate::cipher::{to_text, Cipher, StreamCipher};
+use crate::error::CipherError;
+use crate::symmetric::alphabet::{Alphabet, UnknownSymbol};
 use crate::symmetric::ALPHABET;
-use rand;
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+use rand::rngs::OsRng;
 use rand::Rng;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
 
 /*
     In this method, the secret is the same size as the plain text.
@@ -10,39 +20,553 @@ use rand::Rng;
     which will be used to swap the letters, creating a cipher text.
     Since the generated numbers are random, there is no information leaking in the cipher text, making
     One Time Pad solution  having a perfect secrecy.
+
+    The perfect secrecy only holds if the pad is truly random and never used twice,
+    so the pad is generated by the operating system's secure random generator and stored in a file
+    that remembers how much of it was already consumed:
+
+        ONE TIME PAD
+        alphabet: uppercase
+        length: 5
+        offset: 0
+        checksum: <sha256 of the alphabet and values>
+        values: 3 26 0 14 7
+
+    Each encryption takes the values starting at the offset and moves the offset forward,
+    the offset of the message has to be shared with the receiver so it can decrypt it with the same values.
+    Decrypting only reads the values, the receiver's copy of the pad doesn't change.
+    The checksum covers the offset as well, so it can't be moved back to reuse the values.
 */
 
-fn random_sequence(len: usize) -> Vec<usize> {
-    let mut rng = rand::thread_rng();
-    let mut result = vec![0; len];
+const PAD_HEADER: &str = "ONE TIME PAD";
+
+pub fn generate_pad(length: usize, alphabet: &Alphabet) -> Vec<usize> {
+    (0..length)
+        .map(|_| OsRng.gen_range(0..alphabet.len()))
+        .collect()
+}
+
+pub fn encrypt(plain_text: &str, secret: &[usize]) -> Result<String, CipherError> {
+    encrypt_with(
+        plain_text,
+        secret,
+        &Alphabet::default(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+pub fn decrypt(cipher_text: &str, secret: &[usize]) -> Result<String, CipherError> {
+    decrypt_with(
+        cipher_text,
+        secret,
+        &Alphabet::default(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+// Only letters consume the secret, they keep their case and the rest of the text is not encrypted.
+pub fn encrypt_preserving_case(plain_text: &str, secret: &[usize]) -> Result<String, CipherError> {
+    encrypt_with(
+        plain_text,
+        secret,
+        &Alphabet::letters(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+pub fn decrypt_preserving_case(cipher_text: &str, secret: &[usize]) -> Result<String, CipherError> {
+    decrypt_with(
+        cipher_text,
+        secret,
+        &Alphabet::letters(),
+        UnknownSymbol::PassThrough,
+    )
+}
 
-    for i in 0..len {
-        result[i] = rng.gen_range(0..ALPHABET.len());
+// Only symbols of the alphabet consume values of the secret, so the secret must cover all of them.
+fn check_secret_length(
+    text: &str,
+    secret: &[usize],
+    alphabet: &Alphabet,
+) -> Result<(), CipherError> {
+    let required = text
+        .chars()
+        .filter(|c| alphabet.position(*c).is_some())
+        .count();
+    if secret.len() < required {
+        return Err(CipherError::KeyTooShort {
+            required,
+            available: secret.len(),
+        });
     }
 
-    result
+    Ok(())
+}
+
+pub fn encrypt_with(
+    plain_text: &str,
+    secret: &[usize],
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    check_secret_length(plain_text, secret, alphabet)?;
+    let mut secret = secret.iter();
+    alphabet.transform(plain_text, policy, |index| {
+        index + secret.next().copied().unwrap_or_default()
+    })
+}
+
+pub fn decrypt_with(
+    cipher_text: &str,
+    secret: &[usize],
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    check_secret_length(cipher_text, secret, alphabet)?;
+    let mut secret = secret.iter();
+    alphabet.transform(cipher_text, policy, |index| {
+        let secret_value = secret.next().copied().unwrap_or_default();
+        alphabet.len() + index - secret_value % alphabet.len()
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OneTimePad {
+    pub alphabet: Alphabet,
+    pub policy: UnknownSymbol,
+}
+
+impl OneTimePad {
+    pub fn new(alphabet: Alphabet, policy: UnknownSymbol) -> Self {
+        Self { alphabet, policy }
+    }
+}
+
+impl Cipher for OneTimePad {
+    type Key = Vec<usize>;
+
+    fn encrypt(&self, key: &Vec<usize>, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        encrypt_with(to_text(plain_text)?, key, &self.alphabet, self.policy).map(String::into_bytes)
+    }
+
+    fn decrypt(&self, key: &Vec<usize>, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        decrypt_with(to_text(cipher_text)?, key, &self.alphabet, self.policy)
+            .map(String::into_bytes)
+    }
+}
+
+impl StreamCipher for OneTimePad {
+    fn key_stream(&self, key: &Vec<usize>, length: usize) -> Result<Vec<usize>, CipherError> {
+        if key.len() < length {
+            return Err(CipherError::KeyTooShort {
+                required: length,
+                available: key.len(),
+            });
+        }
+
+        Ok(key[..length]
+            .iter()
+            .map(|k| k % self.alphabet.len())
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pad {
+    alphabet: Alphabet,
+    values: Vec<usize>,
+    offset: usize,
+}
+
+impl Pad {
+    pub fn generate(length: usize, alphabet: Alphabet) -> Self {
+        Self {
+            values: generate_pad(length, &alphabet),
+            alphabet,
+            offset: 0,
+        }
+    }
+
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.values.len() - self.offset
+    }
+
+    // Takes the values needed to encrypt the text, returning the offset where they start,
+    // only unused values are taken so the same values never encrypt two messages.
+    pub fn take(&mut self, text: &str) -> Result<(usize, Vec<usize>), CipherError> {
+        let offset = self.offset;
+        let secret = self.values_at(offset, text)?;
+        self.offset += secret.len();
+        Ok((offset, secret))
+    }
+
+    // Reads the values used by a message encrypted at the given offset without consuming them,
+    // so the receiver can decrypt messages in any order and as many times as needed.
+    pub fn values_at(&self, offset: usize, text: &str) -> Result<Vec<usize>, CipherError> {
+        let required = self.alphabet.count(text);
+        let available = self.values.len().saturating_sub(offset);
+        if required > available {
+            return Err(CipherError::KeyTooShort {
+                required,
+                available,
+            });
+        }
+
+        Ok(self.values[offset..offset + required].to_vec())
+    }
+
+    // The offset is covered too, moving it back would allow the pad to be reused.
+    fn checksum(&self) -> String {
+        let mut digest = Sha256::new();
+        digest.input_str(&self.alphabet.to_string());
+        digest.input(&(self.offset as u64).to_le_bytes());
+        for value in &self.values {
+            digest.input(&(*value as u64).to_le_bytes());
+        }
+        digest.result_str()
+    }
+}
+
+impl fmt::Display for Pad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values = self
+            .values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>();
+
+        writeln!(f, "{}", PAD_HEADER)?;
+        writeln!(f, "alphabet: {}", self.alphabet)?;
+        writeln!(f, "length: {}", self.values.len())?;
+        writeln!(f, "offset: {}", self.offset)?;
+        writeln!(f, "checksum: {}", self.checksum())?;
+        writeln!(f, "values: {}", values.join(" "))
+    }
+}
+
+impl FromStr for Pad {
+    type Err = CipherError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        if lines.next() != Some(PAD_HEADER) {
+            return Err(CipherError::InvalidPadFile("missing header"));
+        }
+
+        let mut field = |name: &str| {
+            lines
+                .next()
+                .and_then(|line| line.strip_prefix(name))
+                .and_then(|line| line.strip_prefix(": "))
+                .ok_or(CipherError::InvalidPadFile("missing field"))
+        };
+
+        let alphabet = field("alphabet")?.parse::<Alphabet>()?;
+        let length = field("length")?
+            .parse::<usize>()
+            .map_err(|_| CipherError::InvalidPadFile("invalid length"))?;
+        let offset = field("offset")?
+            .parse::<usize>()
+            .map_err(|_| CipherError::InvalidPadFile("invalid offset"))?;
+        let checksum = field("checksum")?.to_string();
+        let values = field("values")?
+            .split_whitespace()
+            .map(|v| v.parse::<usize>())
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| CipherError::InvalidPadFile("invalid values"))?;
+
+        if values.len() != length {
+            return Err(CipherError::InvalidPadFile(
+                "length doesn't match the values",
+            ));
+        }
+
+        if offset > length {
+            return Err(CipherError::InvalidPadFile(
+                "offset beyond the end of the pad",
+            ));
+        }
+
+        if values.iter().any(|v| *v >= alphabet.len()) {
+            return Err(CipherError::InvalidPadFile(
+                "values outside of the alphabet",
+            ));
+        }
+
+        let pad = Self {
+            alphabet,
+            values,
+            offset,
+        };
+
+        if pad.checksum() != checksum {
+            return Err(CipherError::ChecksumMismatch);
+        }
+
+        Ok(pad)
+    }
+}
+
+/*
+    Two time pad
+
+    When the same secret encrypts two messages, the difference between the cipher texts
+    cancels the secret out:
+
+        C1 - C2 = (P1 + K) - (P2 + K) = P1 - P2 (mod 27)
+
+    So the difference only depends on the plain texts and the perfect secrecy is gone.
+
+    Crib dragging: we guess a word that probably is in one of the messages (a crib, like "THE")
+    and slide it over every position of the difference, at the right position the other message shows up
+    as readable text: P2 = crib - (P1 - P2), or P1 = crib + (P1 - P2) if the crib was in the second message.
+
+    Automated attack: we extend the first plain text one english word at a time and keep
+    only the extensions that make the second plain text look like english words as well.
+    The word list is small, so when a message has a word outside of it we fall back to extending
+    the first plain text one symbol at a time, keeping the candidates where both texts score best
+    with english quadgrams (known words get a bonus). Many pairs of texts fit the same difference,
+    so this recovery is partial: some words come out right, others are english looking nonsense.
+*/
+
+const BEAM_WIDTH: usize = 200;
+const SYMBOL_BEAM_WIDTH: usize = 1000;
+// Added to the quadgram score for every symbol of a known word.
+const WORD_BONUS: f64 = 1.0;
+
+struct Dictionary {
+    words: HashSet<Vec<usize>>,
+    prefixes: HashSet<Vec<usize>>,
+}
+
+impl Dictionary {
+    fn english() -> Self {
+        let mut words = HashSet::new();
+        let mut prefixes = HashSet::new();
+        for word in include_str!("english_words.txt").lines() {
+            let word = to_symbols(word);
+            for i in 0..=word.len() {
+                prefixes.insert(word[..i].to_vec());
+            }
+            words.insert(word);
+        }
+
+        Self { words, prefixes }
+    }
+
+    // Tokens are split by spaces (symbol 0), every complete one must be a word
+    // and the last one, which may still grow, must be the beginning of a word.
+    fn is_valid(&self, symbols: &[usize]) -> bool {
+        let tokens = symbols.split(|s| *s == 0).collect::<Vec<&[usize]>>();
+        let (last, complete) = tokens.split_last().expect("split always yields a token");
+        complete.iter().all(|t| self.words.contains(*t)) && self.prefixes.contains(*last)
+    }
+
+    // Score gained by the last symbol of a text that starts with a space, a space completing a known word
+    // gets the bonus of the whole word.
+    fn extension_score(&self, text: &[usize]) -> f64 {
+        let (last, previous) = text.split_last().expect("the text starts with a space");
+        let bonus = match (last, previous.rsplit(|s| *s == 0).next()) {
+            (0, Some(word)) if self.words.contains(word) => word.len() as f64 * WORD_BONUS,
+            _ => 0.0,
+        };
+        english_score(&text[text.len().saturating_sub(4)..]) + bonus
+    }
+}
+
+// Quadgram score of the symbols, fragments too short for a quadgram are scored with bigrams.
+fn english_score(symbols: &[usize]) -> f64 {
+    if symbols.len() >= 4 {
+        Quadgrams::english().score_symbols(symbols)
+    } else {
+        Bigrams::english().score_symbols(symbols)
+    }
 }
 
-pub fn encrypt(plain_text: &str, secret: &[usize]) -> String {
-    let mut result = String::new();
+fn to_symbols(text: &str) -> Vec<usize> {
+    text.to_uppercase()
+        .chars()
+        .filter_map(|c| ALPHABET.iter().position(|a| *a == c))
+        .collect()
+}
+
+fn from_symbols(symbols: &[usize]) -> String {
+    symbols.iter().map(|s| ALPHABET[*s]).collect()
+}
+
+// P1 - P2 for the symbols both cipher texts have in common.
+pub fn difference(first_cipher_text: &str, second_cipher_text: &str) -> Vec<usize> {
+    to_symbols(first_cipher_text)
+        .into_iter()
+        .zip(to_symbols(second_cipher_text))
+        .map(|(a, b)| (ALPHABET.len() + a - b) % ALPHABET.len())
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CribMatch {
+    pub position: usize,
+    // true when the crib was placed in the first message, so the fragment belongs to the second one.
+    pub crib_in_first: bool,
+    pub fragment: String,
+    pub score: f64,
+}
+
+// Slides the crib over the difference, returning every placement with the most english looking fragments first.
+pub fn crib_drag(difference: &[usize], crib: &str) -> Vec<CribMatch> {
+    let crib = to_symbols(crib);
+    let n = ALPHABET.len();
+
+    let mut result = vec![];
+    if crib.is_empty() || crib.len() > difference.len() {
+        return result;
+    }
+
+    for position in 0..=difference.len() - crib.len() {
+        let window = &difference[position..position + crib.len()];
+        for crib_in_first in [true, false] {
+            let fragment = crib
+                .iter()
+                .zip(window)
+                .map(|(c, d)| {
+                    if crib_in_first {
+                        (n + c - d) % n
+                    } else {
+                        (c + d) % n
+                    }
+                })
+                .collect::<Vec<usize>>();
 
-    for (index, char) in plain_text.to_uppercase().chars().enumerate() {
-        let char_index = ALPHABET.iter().position(|a| *a == char).unwrap_or_default();
-        result.push(ALPHABET[(char_index + secret[index]) % ALPHABET.len()]);
+            result.push(CribMatch {
+                position,
+                crib_in_first,
+                score: english_score(&fragment),
+                fragment: from_symbols(&fragment),
+            });
+        }
     }
 
+    result.sort_by(|a, b| b.score.total_cmp(&a.score));
     result
 }
 
-pub fn decrypt(cipher_text: &str, secret: &[usize]) -> String {
-    let mut result = String::new();
+// Recovers both plain texts encrypted with the same secret, most likely pairs first.
+// Only the part both cipher texts have in common can be recovered.
+// When no pair is made only of known words, the pairs come from the partial recovery by quadgrams.
+pub fn recover_plain_texts(
+    first_cipher_text: &str,
+    second_cipher_text: &str,
+) -> Vec<(String, String)> {
+    let difference = difference(first_cipher_text, second_cipher_text);
+    let dictionary = Dictionary::english();
+    let words = dictionary.words.iter().collect::<Vec<&Vec<usize>>>();
+    let score = |first: &[usize]| {
+        log_likelihood(first) + log_likelihood(&second_plain_text(first, &difference))
+    };
+
+    // candidates for the first plain text, grouped by how many symbols they already cover
+    let mut frontier = vec![vec![]; difference.len() + 1];
+    frontier[0].push(vec![]);
+
+    for length in 0..difference.len() {
+        let mut states = std::mem::take(&mut frontier[length])
+            .into_iter()
+            .map(|first| (score(&first), first))
+            .collect::<Vec<(f64, Vec<usize>)>>();
+        states.sort_by(|a, b| b.0.total_cmp(&a.0));
+        states.truncate(BEAM_WIDTH);
+
+        for (_, first) in states {
+            for word in &words {
+                // the last word of the message may be cut by the end of the difference
+                let mut extended = first.clone();
+                extended.extend(word.iter());
+                extended.push(0);
+                extended.truncate(difference.len());
+
+                let second = second_plain_text(&extended, &difference);
+                let token_start = second[..length]
+                    .iter()
+                    .rposition(|s| *s == 0)
+                    .map_or(0, |i| i + 1);
+                if dictionary.is_valid(&second[token_start..]) {
+                    let end = extended.len();
+                    if !frontier[end].contains(&extended) {
+                        frontier[end].push(extended);
+                    }
+                }
+            }
+        }
+    }
 
-    for (index, char) in cipher_text.to_uppercase().chars().enumerate() {
-        let char_index = ALPHABET.iter().position(|a| *a == char).unwrap_or_default();
-        result.push(ALPHABET[(ALPHABET.len() + char_index - secret[index]) % ALPHABET.len()]);
+    if frontier[difference.len()].is_empty() {
+        return recover_by_quadgrams(&difference, &dictionary);
     }
 
+    let mut result = frontier[difference.len()]
+        .iter()
+        .map(|first| {
+            let second = second_plain_text(first, &difference);
+            (from_symbols(first), from_symbols(&second), score(first))
+        })
+        .collect::<Vec<(String, String, f64)>>();
+
+    result.sort_by(|a, b| b.2.total_cmp(&a.2));
     result
+        .into_iter()
+        .map(|(first, second, _)| (first, second))
+        .collect()
+}
+
+// Extends both plain texts one symbol at a time, each text starts with a space like a word in the middle of a text.
+fn recover_by_quadgrams(difference: &[usize], dictionary: &Dictionary) -> Vec<(String, String)> {
+    let n = ALPHABET.len();
+    let mut states = vec![(0.0, vec![0], vec![0])];
+    for d in difference {
+        let mut extended = states
+            .iter()
+            .flat_map(|(score, first, second)| {
+                (0..n).map(move |symbol| {
+                    let first = [&first[..], &[symbol]].concat();
+                    let second = [&second[..], &[(n + symbol - d) % n]].concat();
+                    let score = score
+                        + dictionary.extension_score(&first)
+                        + dictionary.extension_score(&second);
+                    (score, first, second)
+                })
+            })
+            .collect::<Vec<(f64, Vec<usize>, Vec<usize>)>>();
+        extended.sort_by(|a, b| b.0.total_cmp(&a.0));
+        extended.truncate(SYMBOL_BEAM_WIDTH);
+        states = extended;
+    }
+
+    states
+        .into_iter()
+        .map(|(_, first, second)| (from_symbols(&first[1..]), from_symbols(&second[1..])))
+        .collect()
+}
+
+// How likely the symbols are in english, the higher the better.
+fn log_likelihood(symbols: &[usize]) -> f64 {
+    let frequencies = Language::English.frequencies();
+    symbols.iter().map(|s| frequencies[*s].ln()).sum()
+}
+
+fn second_plain_text(first: &[usize], difference: &[usize]) -> Vec<usize> {
+    first
+        .iter()
+        .zip(difference)
+        .map(|(p, d)| (ALPHABET.len() + p - d) % ALPHABET.len())
+        .collect()
 }
 
 #[cfg(test)]
@@ -54,15 +578,231 @@ mod tests {
         let message = "Hello World";
         let secret = vec![8, 0, 0, 2, 3, 8, 7, 8, 1, 1, 4];
 
-        let encrypted = encrypt(message, &secret);
+        let encrypted = encrypt(message, &secret).unwrap();
         assert_eq!(encrypted, "PELNRHCWSMH");
 
-        let decrypted = decrypt(&encrypted, &secret);
+        let decrypted = decrypt(&encrypted, &secret).unwrap();
         assert_eq!(decrypted, "HELLO WORLD");
 
-        let secret = random_sequence(message.len());
-        let encrypted = encrypt(message, &secret);
-        let decrypted = decrypt(&encrypted, &secret);
+        let secret = generate_pad(message.len(), &Alphabet::default());
+        let encrypted = encrypt(message, &secret).unwrap();
+        let decrypted = decrypt(&encrypted, &secret).unwrap();
         assert_eq!(decrypted, "HELLO WORLD");
     }
+
+    #[test]
+    fn reject_short_secret() {
+        let secret = vec![8, 0, 0];
+        assert_eq!(
+            encrypt("Hello World", &secret),
+            Err(CipherError::KeyTooShort {
+                required: 11,
+                available: 3
+            })
+        );
+        assert_eq!(
+            decrypt_preserving_case("Pel, no!", &secret),
+            Err(CipherError::KeyTooShort {
+                required: 5,
+                available: 3
+            })
+        );
+    }
+
+    #[test]
+    fn encrypt_decrypt_with_alphabet() {
+        let message = "Hello, World!";
+        let alphabet = Alphabet::bytes();
+        let secret = (0..message.len()).map(|i| i * 31).collect::<Vec<usize>>();
+
+        let encrypted = encrypt_with(message, &secret, &alphabet, UnknownSymbol::Reject).unwrap();
+        let decrypted =
+            decrypt_with(&encrypted, &secret, &alphabet, UnknownSymbol::Reject).unwrap();
+        assert_eq!(decrypted, message);
+
+        let alphabet = Alphabet::uppercase();
+        let encrypted = encrypt_with(message, &secret, &alphabet, UnknownSymbol::Drop).unwrap();
+        assert_eq!(encrypted.len(), 11);
+        let decrypted = decrypt_with(&encrypted, &secret, &alphabet, UnknownSymbol::Drop).unwrap();
+        assert_eq!(decrypted, "HELLO WORLD");
+    }
+
+    #[test]
+    fn encrypt_decrypt_through_traits() {
+        let cipher = OneTimePad::default();
+        let secret = vec![8, 0, 0, 2, 3, 8, 7, 8, 1, 1, 4];
+        let encrypted = Cipher::encrypt(&cipher, &secret, b"Hello World").unwrap();
+        assert_eq!(encrypted, b"PELNRHCWSMH");
+        assert_eq!(
+            Cipher::decrypt(&cipher, &secret, &encrypted).unwrap(),
+            b"HELLO WORLD"
+        );
+        assert_eq!(cipher.key_stream(&secret, 3).unwrap(), vec![8, 0, 0]);
+        assert!(cipher.key_stream(&secret, 12).is_err());
+    }
+
+    #[test]
+    fn generate_and_consume_pad() {
+        let mut sender = Pad::generate(20, Alphabet::default());
+        let receiver = sender.to_string().parse::<Pad>().unwrap();
+        assert_eq!(sender, receiver);
+
+        let (offset, secret) = sender.take("Hello, World").unwrap();
+        assert_eq!(offset, 0);
+        let encrypted = encrypt("Hello, World", &secret).unwrap();
+
+        let (offset, secret) = sender.take("Bye").unwrap();
+        assert_eq!(offset, 11);
+        assert_eq!(sender.remaining(), 6);
+        let encrypted_bye = encrypt("Bye", &secret).unwrap();
+        assert_eq!(
+            sender.take("Too long message"),
+            Err(CipherError::KeyTooShort {
+                required: 16,
+                available: 6
+            })
+        );
+
+        // messages can be decrypted out of order and more than once
+        let secret = receiver.values_at(11, &encrypted_bye).unwrap();
+        assert_eq!(decrypt(&encrypted_bye, &secret).unwrap(), "BYE");
+        let secret = receiver.values_at(0, &encrypted).unwrap();
+        assert_eq!(decrypt(&encrypted, &secret).unwrap(), "HELLO, WORLD");
+        assert_eq!(receiver.values_at(0, &encrypted).unwrap(), secret);
+        assert_eq!(receiver.offset(), 0);
+        assert_eq!(
+            receiver.values_at(18, &encrypted_bye),
+            Err(CipherError::KeyTooShort {
+                required: 3,
+                available: 2
+            })
+        );
+    }
+
+    #[test]
+    fn parse_pad_file() {
+        let pad = Pad::generate(10, Alphabet::letters());
+        let file = pad.to_string();
+        assert!(file.starts_with("ONE TIME PAD\nalphabet: letters\nlength: 10\noffset: 0\n"));
+        assert_eq!(file.parse::<Pad>(), Ok(pad.clone()));
+
+        let tampered = file.replace("offset: 0", "offset: 11");
+        assert_eq!(
+            tampered.parse::<Pad>(),
+            Err(CipherError::InvalidPadFile(
+                "offset beyond the end of the pad"
+            ))
+        );
+        let tampered = file.replace("length: 10", "length: 9");
+        assert_eq!(
+            tampered.parse::<Pad>(),
+            Err(CipherError::InvalidPadFile(
+                "length doesn't match the values"
+            ))
+        );
+
+        // moving the offset back to reuse the pad is detected
+        let mut used = pad.clone();
+        used.take("ABCD").unwrap();
+        let tampered = used.to_string().replace("offset: 4", "offset: 0");
+        assert_eq!(tampered.parse::<Pad>(), Err(CipherError::ChecksumMismatch));
+
+        let mut lines = file.lines().map(String::from).collect::<Vec<String>>();
+        lines[5] = format!("values: {}", ["1"; 10].join(" "));
+        assert_eq!(
+            lines.join("\n").parse::<Pad>(),
+            Err(CipherError::ChecksumMismatch)
+        );
+
+        assert_eq!(
+            "one time pad".parse::<Pad>(),
+            Err(CipherError::InvalidPadFile("missing header"))
+        );
+    }
+
+    #[test]
+    fn crib_drag_reused_pad() {
+        let first = "MEET ME AT THE RIVER AT DAWN";
+        let second = "SEND MORE MEN TO THE NORTH";
+        let secret = generate_pad(first.len(), &Alphabet::default());
+        let difference = difference(
+            &encrypt(first, &secret).unwrap(),
+            &encrypt(second, &secret).unwrap(),
+        );
+        assert_eq!(difference.len(), second.len());
+
+        let matches = crib_drag(&difference, "MORE MEN");
+        assert_eq!(matches.len(), 2 * (second.len() - 7));
+        assert_eq!((matches[0].position, matches[0].crib_in_first), (5, false));
+        assert_eq!(matches[0].fragment, "ME AT TH");
+        assert!(matches[0].score > matches[1].score);
+
+        let matches = crib_drag(&difference, "THE");
+        let placement = matches
+            .iter()
+            .find(|m| m.position == 11 && m.crib_in_first)
+            .unwrap();
+        assert_eq!(placement.fragment, "EN ");
+    }
+
+    #[test]
+    fn recover_reused_pad_messages() {
+        let first = "MEET ME AT THE RIVER AT DAWN";
+        let second = "SEND MORE MEN TO THE NORTH";
+        let secret = generate_pad(first.len(), &Alphabet::default());
+        let first_cipher_text = encrypt(first, &secret).unwrap();
+        let second_cipher_text = encrypt(second, &secret).unwrap();
+
+        let candidates = recover_plain_texts(&first_cipher_text, &second_cipher_text);
+        assert!(candidates.contains(&(first[..second.len()].to_string(), second.to_string())));
+        assert_eq!(
+            candidates[0],
+            (first[..second.len()].to_string(), second.to_string())
+        );
+    }
+
+    #[test]
+    fn recover_messages_outside_the_word_list() {
+        // most of these words aren't in the word list, the difference doesn't depend on the pad
+        let first = "THE WEATHER FORECAST PROMISES HEAVY RAIN TOMORROW";
+        let second = "OUR ENGINEERS FINISHED TESTING THE NEW PROTOTYPE";
+        let secret = generate_pad(first.len(), &Alphabet::default());
+        let first_cipher_text = encrypt(first, &secret).unwrap();
+        let second_cipher_text = encrypt(second, &secret).unwrap();
+
+        let matches = crib_drag(
+            &difference(&first_cipher_text, &second_cipher_text),
+            "WEATHER",
+        );
+        assert_eq!((matches[0].position, matches[0].crib_in_first), (4, true));
+        assert_eq!(matches[0].fragment, "ENGINEE");
+
+        // only part of the texts comes back, but well above the 1 in 27 of a random guess
+        let (first_guess, second_guess) =
+            &recover_plain_texts(&first_cipher_text, &second_cipher_text)[0];
+        assert_eq!(&first_guess[36..40], "RAIN");
+        let correct = [(first, first_guess), (second, second_guess)]
+            .iter()
+            .map(|(text, guess)| {
+                text.chars()
+                    .zip(guess.chars())
+                    .filter(|(a, b)| a == b)
+                    .count()
+            })
+            .sum::<usize>();
+        assert!(correct * 3 > first.len() + second.len());
+    }
+
+    #[test]
+    fn encrypt_decrypt_preserving_case() {
+        let message = "Hello, World!";
+        let secret = vec![8, 0, 0, 2, 3, 8, 7, 8, 1, 1];
+
+        let encrypted = encrypt_preserving_case(message, &secret).unwrap();
+        assert_eq!(encrypted, "Pelnr, Evzme!");
+        assert_eq!(
+            decrypt_preserving_case(&encrypted, &secret).unwrap(),
+            message
+        );
+    }
 }
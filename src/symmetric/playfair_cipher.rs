@@ -0,0 +1,332 @@
+use crate::cipher::{to_text, Cipher};
+use crate::error::CipherError;
+
+/*
+    Playfair cipher encrypts pairs of letters (digraphs) using a 5x5 key square.
+
+    Key square
+    The keyword without repeated letters followed by the rest of the alphabet, written row by row.
+    There are 26 letters and only 25 cells, so J is merged with I.
+
+    keyword: PLAYFAIR EXAMPLE
+
+    P L A Y F
+    I R E X M
+    B C D G H
+    K N O Q S
+    T U V W Z
+
+    Preparing the text
+    Only letters are encrypted, everything else is removed and J becomes I.
+    The text is split in pairs, when both letters of a pair are equal a filler (X) is inserted between them,
+    and when the last pair is missing a letter the filler is appended.
+    HELLO -> HE LX LO
+    If the repeated letter is the filler itself, Q is used instead.
+
+    Encryption, for each pair:
+    -> same row: each letter is replaced by the one on its right (wrapping around).
+    -> same column: each letter is replaced by the one below it (wrapping around).
+    -> otherwise: the letters are the corners of a rectangle, each one is replaced by the corner
+       in the same row and in the column of the other letter.
+
+    Decryption does the same moving left and up, and the fillers can be removed afterwards
+    when they are between two equal letters or at the end of the text.
+    That cleanup is a guess, a real X between two equal letters (like in "AXA") is removed as well,
+    and spaces, punctuation and the difference between I and J are lost for good.
+*/
+
+const SIZE: usize = 5;
+const FILLER: char = 'X';
+const ALTERNATIVE_FILLER: char = 'Q';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySquare {
+    letters: Vec<char>,
+}
+
+// Uppercase letter of the square, J is merged with I and anything else is not part of the square.
+fn normalize(c: char) -> Option<char> {
+    match c.to_ascii_uppercase() {
+        'J' => Some('I'),
+        c if c.is_ascii_uppercase() => Some(c),
+        _ => None,
+    }
+}
+
+impl KeySquare {
+    // Spaces in the keyword are ignored, any other symbol that isn't a letter is rejected.
+    pub fn new(keyword: &str) -> Result<Self, CipherError> {
+        let mut letters = Vec::with_capacity(SIZE * SIZE);
+        for c in keyword.chars().filter(|c| *c != ' ') {
+            let letter = normalize(c).ok_or(CipherError::InvalidKeySymbol(c))?;
+            if !letters.contains(&letter) {
+                letters.push(letter);
+            }
+        }
+
+        if letters.is_empty() {
+            return Err(CipherError::InvalidKey("secret must not be empty"));
+        }
+
+        for letter in ('A'..='Z').filter(|c| *c != 'J') {
+            if !letters.contains(&letter) {
+                letters.push(letter);
+            }
+        }
+
+        Ok(Self { letters })
+    }
+
+    // (row, column) of a letter of the square.
+    fn position(&self, letter: char) -> (usize, usize) {
+        let index = self
+            .letters
+            .iter()
+            .position(|l| *l == letter)
+            .expect("the square has every letter but J");
+        (index / SIZE, index % SIZE)
+    }
+
+    fn letter(&self, row: usize, column: usize) -> char {
+        self.letters[(row % SIZE) * SIZE + column % SIZE]
+    }
+
+    // `shift` is 1 to encrypt and SIZE - 1 (one step back) to decrypt.
+    fn transform(&self, (first, second): (char, char), shift: usize) -> (char, char) {
+        let (row1, column1) = self.position(first);
+        let (row2, column2) = self.position(second);
+
+        if row1 == row2 {
+            (
+                self.letter(row1, column1 + shift),
+                self.letter(row2, column2 + shift),
+            )
+        } else if column1 == column2 {
+            (
+                self.letter(row1 + shift, column1),
+                self.letter(row2 + shift, column2),
+            )
+        } else {
+            (self.letter(row1, column2), self.letter(row2, column1))
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn rows(&self) -> Vec<String> {
+        self.letters
+            .chunks(SIZE)
+            .map(|row| row.iter().collect())
+            .collect()
+    }
+}
+
+fn letters(text: &str) -> Vec<char> {
+    text.chars().filter_map(normalize).collect()
+}
+
+// Splits the text in pairs of different letters, adding fillers where needed.
+fn digraphs(text: &str, filler: char) -> Vec<(char, char)> {
+    let letters = letters(text);
+    let mut result = vec![];
+    let mut i = 0;
+
+    while i < letters.len() {
+        let first = letters[i];
+        let filler = if first == filler {
+            ALTERNATIVE_FILLER
+        } else {
+            filler
+        };
+
+        match letters.get(i + 1) {
+            Some(second) if *second != first => {
+                result.push((first, *second));
+                i += 2;
+            }
+            _ => {
+                result.push((first, filler));
+                i += 1;
+            }
+        }
+    }
+
+    result
+}
+
+fn check_filler(filler: char) -> Result<char, CipherError> {
+    match normalize(filler) {
+        Some(letter) if letter == filler && letter != ALTERNATIVE_FILLER => Ok(letter),
+        _ => Err(CipherError::InvalidKey(
+            "filler must be an uppercase letter other than J and Q",
+        )),
+    }
+}
+
+#[allow(dead_code)]
+pub fn encrypt(plain_text: &str, secret: &str) -> Result<String, CipherError> {
+    encrypt_with(plain_text, secret, FILLER)
+}
+
+#[allow(dead_code)]
+pub fn decrypt(cipher_text: &str, secret: &str) -> Result<String, CipherError> {
+    decrypt_with(cipher_text, secret, FILLER)
+}
+
+pub fn encrypt_with(plain_text: &str, secret: &str, filler: char) -> Result<String, CipherError> {
+    let square = KeySquare::new(secret)?;
+    let filler = check_filler(filler)?;
+
+    Ok(digraphs(plain_text, filler)
+        .into_iter()
+        .flat_map(|pair| {
+            let (first, second) = square.transform(pair, 1);
+            [first, second]
+        })
+        .collect())
+}
+
+// The fillers are kept, use `remove_fillers` to clean up the decrypted text.
+pub fn decrypt_with(cipher_text: &str, secret: &str, filler: char) -> Result<String, CipherError> {
+    let square = KeySquare::new(secret)?;
+    check_filler(filler)?;
+
+    let letters = letters(cipher_text);
+    if !letters.len().is_multiple_of(2) {
+        return Err(CipherError::InvalidBlock);
+    }
+
+    Ok(letters
+        .chunks(2)
+        .flat_map(|pair| {
+            let (first, second) = square.transform((pair[0], pair[1]), SIZE - 1);
+            [first, second]
+        })
+        .collect())
+}
+
+// Removes the fillers that are between two equal letters or at the end of a decrypted text.
+pub fn remove_fillers(text: &str, filler: char) -> String {
+    let letters = text.chars().collect::<Vec<char>>();
+    let is_filler = |i: usize| {
+        let expected = if i > 0 && letters[i - 1] == filler {
+            ALTERNATIVE_FILLER
+        } else {
+            filler
+        };
+        letters[i] == expected
+    };
+
+    letters
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            // only the second letter of a pair can be a filler
+            if i % 2 == 0 || !is_filler(*i) {
+                return true;
+            }
+
+            let between_equal = *i + 1 < letters.len() && letters[*i - 1] == letters[*i + 1];
+            let last = *i + 1 == letters.len();
+            !(between_equal || last)
+        })
+        .map(|(_, c)| *c)
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayfairCipher {
+    pub filler: char,
+}
+
+impl PlayfairCipher {
+    pub fn new(filler: char) -> Self {
+        Self { filler }
+    }
+}
+
+impl Default for PlayfairCipher {
+    fn default() -> Self {
+        Self::new(FILLER)
+    }
+}
+
+impl Cipher for PlayfairCipher {
+    type Key = String;
+
+    fn encrypt(&self, key: &String, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        encrypt_with(to_text(plain_text)?, key, self.filler).map(String::into_bytes)
+    }
+
+    // The fillers are removed from the decrypted text.
+    fn decrypt(&self, key: &String, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        let plain_text = decrypt_with(to_text(cipher_text)?, key, self.filler)?;
+        Ok(remove_fillers(&plain_text, self.filler).into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_square() {
+        let square = KeySquare::new("Playfair Example").unwrap();
+        assert_eq!(
+            square.rows(),
+            vec!["PLAYF", "IREXM", "BCDGH", "KNOQS", "TUVWZ"]
+        );
+
+        assert_eq!(
+            KeySquare::new(" "),
+            Err(CipherError::InvalidKey("secret must not be empty"))
+        );
+        assert_eq!(
+            KeySquare::new("PLAY-FAIR"),
+            Err(CipherError::InvalidKeySymbol('-'))
+        );
+    }
+
+    #[test]
+    fn encrypt_decrypt_message() {
+        let secret = "PLAYFAIR EXAMPLE";
+        let result = encrypt("Hide the gold in the tree stump", secret).unwrap();
+        assert_eq!(result, "BMODZBXDNABEKUDMUIXMMOUVIF");
+
+        let decrypted = decrypt(&result, secret).unwrap();
+        assert_eq!(decrypted, "HIDETHEGOLDINTHETREXESTUMP");
+        assert_eq!(
+            remove_fillers(&decrypted, FILLER),
+            "HIDETHEGOLDINTHETREESTUMP"
+        );
+
+        assert_eq!(decrypt("ABC", secret), Err(CipherError::InvalidBlock));
+        assert_eq!(
+            encrypt_with("HELLO", secret, 'J'),
+            Err(CipherError::InvalidKey(
+                "filler must be an uppercase letter other than J and Q"
+            ))
+        );
+    }
+
+    #[test]
+    fn fillers() {
+        assert_eq!(
+            digraphs("Hello", FILLER),
+            vec![('H', 'E'), ('L', 'X'), ('L', 'O')]
+        );
+        assert_eq!(
+            digraphs("TAXXI", FILLER),
+            vec![('T', 'A'), ('X', 'Q'), ('X', 'I')]
+        );
+        assert_eq!(digraphs("jam", FILLER), vec![('I', 'A'), ('M', 'X')]);
+
+        let cipher = PlayfairCipher::default();
+        let secret = "MONARCHY".to_string();
+        for message in ["BALLOON", "TAXXI", "JAZZ", "ATTACK AT DAWN"] {
+            let encrypted = cipher.encrypt(&secret, message.as_bytes()).unwrap();
+            let decrypted = cipher.decrypt(&secret, &encrypted).unwrap();
+            let expected = letters(message).into_iter().collect::<String>();
+            assert_eq!(String::from_utf8(decrypted).unwrap(), expected);
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use crate::analysis::fitness::Quadgrams;
+use crate::analysis::{self, Language};
+use crate::cipher::{to_text, Cipher, KeyGen};
+use crate::error::CipherError;
+use crate::symmetric::alphabet::{Alphabet, UnknownSymbol};
+use crate::symmetric::ALPHABET;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/*
+    Monoalphabetic substitution cipher swaps every symbol of the alphabet by another one,
+    the secret is the keyed alphabet: the symbol at position N replaces the N-th symbol of the alphabet.
+
+    Remembering a whole alphabet is hard, so the secret is usually a keyword, the keyed alphabet is
+    the keyword without repeated symbols followed by the rest of the alphabet in order:
+
+    keyword: SECRET
+    alphabet:       ' ABCDEFGHIJKLMNOPQRSTUVWXYZ'
+    keyed alphabet: 'SECRT ABDFGHIJKLMNOPQUVWXYZ'
+
+    So a space becomes S, A becomes E, E becomes a space and so on.
+
+    A full keyed alphabet is also a valid keyword, it expands to itself.
+
+    There are 27! (around 10^28) possible secrets, way too many for brute force,
+    but every symbol is always replaced by the same one, so the frequencies of the language are still there.
+*/
+
+// Position of the symbol that replaces each symbol of the alphabet.
+fn keyed_alphabet(secret: &str, alphabet: &Alphabet) -> Result<Vec<usize>, CipherError> {
+    if secret.is_empty() {
+        return Err(CipherError::InvalidKey("secret must not be empty"));
+    }
+
+    let mut result = Vec::with_capacity(alphabet.len());
+    for c in secret.chars() {
+        let index = alphabet
+            .position(c)
+            .ok_or(CipherError::InvalidKeySymbol(c))?;
+        if !result.contains(&index) {
+            result.push(index);
+        }
+    }
+
+    for index in 0..alphabet.len() {
+        if !result.contains(&index) {
+            result.push(index);
+        }
+    }
+
+    Ok(result)
+}
+
+fn inverse(key: &[usize]) -> Vec<usize> {
+    let mut result = vec![0; key.len()];
+    for (plain, cipher) in key.iter().enumerate() {
+        result[*cipher] = plain;
+    }
+    result
+}
+
+fn from_indexes(indexes: &[usize], alphabet: &Alphabet) -> String {
+    indexes.iter().map(|i| alphabet.symbol(*i)).collect()
+}
+
+#[allow(dead_code)]
+pub fn encrypt(plain_text: &str, secret: &str) -> Result<String, CipherError> {
+    encrypt_with(
+        plain_text,
+        secret,
+        &Alphabet::default(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+pub fn decrypt(cipher_text: &str, secret: &str) -> Result<String, CipherError> {
+    decrypt_with(
+        cipher_text,
+        secret,
+        &Alphabet::default(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+pub fn encrypt_with(
+    plain_text: &str,
+    secret: &str,
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    let key = keyed_alphabet(secret, alphabet)?;
+    alphabet.transform(plain_text, policy, |index| key[index])
+}
+
+pub fn decrypt_with(
+    cipher_text: &str,
+    secret: &str,
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    let key = inverse(&keyed_alphabet(secret, alphabet)?);
+    alphabet.transform(cipher_text, policy, |index| key[index])
+}
+
+// Full keyed alphabet generated from a keyword.
+#[allow(dead_code)]
+pub fn expand_secret(secret: &str, alphabet: &Alphabet) -> Result<String, CipherError> {
+    Ok(from_indexes(&keyed_alphabet(secret, alphabet)?, alphabet))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SubstitutionCipher {
+    pub alphabet: Alphabet,
+    pub policy: UnknownSymbol,
+}
+
+impl SubstitutionCipher {
+    pub fn new(alphabet: Alphabet, policy: UnknownSymbol) -> Self {
+        Self { alphabet, policy }
+    }
+}
+
+impl Cipher for SubstitutionCipher {
+    type Key = String;
+
+    fn encrypt(&self, key: &String, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        encrypt_with(to_text(plain_text)?, key, &self.alphabet, self.policy).map(String::into_bytes)
+    }
+
+    fn decrypt(&self, key: &String, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        decrypt_with(to_text(cipher_text)?, key, &self.alphabet, self.policy)
+            .map(String::into_bytes)
+    }
+}
+
+impl KeyGen for SubstitutionCipher {
+    // A random keyed alphabet, keywords leave the end of the alphabet almost untouched.
+    fn generate_key(&self) -> String {
+        let mut key = (0..self.alphabet.len()).collect::<Vec<usize>>();
+        key.shuffle(&mut rand::thread_rng());
+        from_indexes(&key, &self.alphabet)
+    }
+}
+
+/*
+    Breaking substitution cipher (hill climbing)
+
+    1. Start with a guess: the most frequent cipher symbol is replaced by the most frequent english symbol,
+       the second one by the second one and so on.
+    2. Swap two symbols of the guess and score the decrypted text with english quadgrams,
+       if the score is better keep the swap, otherwise undo it.
+    3. Repeat until no swap improves the score, that is a local maximum.
+
+    A local maximum is not always the right secret, so the climb is restarted a few times from
+    a shuffled copy of the best guess so far and the best results are returned.
+    A few hundred symbols are usually enough, shorter texts may have more than one readable solution.
+*/
+
+const RESTARTS: usize = 20;
+// Number of symbols of the best guess that are swapped at random before each restart.
+const RESTART_SWAPS: usize = 6;
+
+// Cipher symbol -> plain symbol, based only on the frequencies of the symbols.
+fn frequency_guess(symbols: &[usize]) -> Vec<usize> {
+    let mut counts = vec![0usize; ALPHABET.len()];
+    for s in symbols {
+        counts[*s] += 1;
+    }
+
+    let by_count = |values: &mut Vec<usize>, score: &dyn Fn(usize) -> f64| {
+        values.sort_by(|a, b| score(*b).total_cmp(&score(*a)).then(a.cmp(b)))
+    };
+
+    let mut cipher_symbols = (0..ALPHABET.len()).collect::<Vec<usize>>();
+    by_count(&mut cipher_symbols, &|s| counts[s] as f64);
+
+    let english = Language::English.frequencies();
+    let mut plain_symbols = (0..ALPHABET.len()).collect::<Vec<usize>>();
+    by_count(&mut plain_symbols, &|s| english[s]);
+
+    let mut result = vec![0; ALPHABET.len()];
+    for (cipher, plain) in cipher_symbols.into_iter().zip(plain_symbols) {
+        result[cipher] = plain;
+    }
+    result
+}
+
+fn score(symbols: &[usize], key: &[usize], buffer: &mut [usize]) -> f64 {
+    for (plain, cipher) in buffer.iter_mut().zip(symbols) {
+        *plain = key[*cipher];
+    }
+    Quadgrams::english().score_symbols(buffer)
+}
+
+// Keeps swapping pairs of symbols of the key while the score improves.
+fn climb(symbols: &[usize], mut key: Vec<usize>) -> (Vec<usize>, f64) {
+    let mut buffer = vec![0; symbols.len()];
+    let mut best = score(symbols, &key, &mut buffer);
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..key.len() {
+            for j in i + 1..key.len() {
+                key.swap(i, j);
+                let candidate = score(symbols, &key, &mut buffer);
+                if candidate > best {
+                    best = candidate;
+                    improved = true;
+                } else {
+                    key.swap(i, j);
+                }
+            }
+        }
+    }
+
+    (key, best)
+}
+
+// Returns (secret, score, plain text) candidates, the most likely one first.
+pub fn crack(cipher_text: &str) -> Vec<(String, f64, String)> {
+    let alphabet = Alphabet::uppercase();
+    let symbols = analysis::symbols(cipher_text, &alphabet);
+    if symbols.is_empty() {
+        return vec![];
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut results = vec![climb(&symbols, frequency_guess(&symbols))];
+    for _ in 0..RESTARTS {
+        let mut key = results[0].0.clone();
+        for _ in 0..RESTART_SWAPS {
+            key.swap(
+                rng.gen_range(0..ALPHABET.len()),
+                rng.gen_range(0..ALPHABET.len()),
+            );
+        }
+
+        results.push(climb(&symbols, key));
+        results.sort_by(|a, b| b.1.total_cmp(&a.1));
+    }
+
+    let mut candidates = results
+        .into_iter()
+        .filter_map(|(key, score)| {
+            // the climb finds the decryption key, the secret is the encryption one
+            let secret = from_indexes(&inverse(&key), &alphabet);
+            let plain_text = decrypt(cipher_text, &secret).ok()?;
+            Some((secret, score, plain_text))
+        })
+        .collect::<Vec<(String, f64, String)>>();
+
+    candidates.dedup_by(|a, b| a.2 == b.2);
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_message() {
+        let alphabet = Alphabet::uppercase();
+        assert_eq!(
+            expand_secret("SECRET", &alphabet).unwrap(),
+            "SECRT ABDFGHIJKLMNOPQUVWXYZ"
+        );
+
+        let result = encrypt("HELLO WORLD", "SECRET").unwrap();
+        assert_eq!(result, "D IILSWLOIT");
+        assert_eq!(decrypt(&result, "SECRET").unwrap(), "HELLO WORLD");
+
+        // a full keyed alphabet expands to itself
+        let secret = expand_secret("ZEBRAS", &alphabet).unwrap();
+        assert_eq!(expand_secret(&secret, &alphabet).unwrap(), secret);
+        assert_eq!(
+            encrypt("ATTACK AT DAWN", &secret).unwrap(),
+            encrypt("ATTACK AT DAWN", "ZEBRAS").unwrap()
+        );
+
+        assert_eq!(
+            encrypt("HELLO", ""),
+            Err(CipherError::InvalidKey("secret must not be empty"))
+        );
+        assert_eq!(
+            encrypt("HELLO", "SECRET!"),
+            Err(CipherError::InvalidKeySymbol('!'))
+        );
+    }
+
+    #[test]
+    fn substitution_cipher_trait() {
+        let cipher = SubstitutionCipher::new(Alphabet::letters(), UnknownSymbol::PassThrough);
+        let secret = cipher.generate_key();
+        assert_eq!(secret.len(), 26);
+
+        let encrypted = cipher.encrypt(&secret, b"Hello, World!").unwrap();
+        assert_eq!(
+            cipher.decrypt(&secret, &encrypted).unwrap(),
+            b"Hello, World!"
+        );
+    }
+
+    #[test]
+    fn crack_encrypted_message() {
+        let message = "IT IS A TRUTH UNIVERSALLY ACKNOWLEDGED THAT A SINGLE MAN IN POSSESSION OF A GOOD \
+            FORTUNE MUST BE IN WANT OF A WIFE HOWEVER LITTLE KNOWN THE FEELINGS OR VIEWS OF SUCH A MAN \
+            MAY BE ON HIS FIRST ENTERING A NEIGHBOURHOOD THIS TRUTH IS SO WELL FIXED IN THE MINDS OF THE \
+            SURROUNDING FAMILIES THAT HE IS CONSIDERED AS THE RIGHTFUL PROPERTY OF SOME ONE OR OTHER \
+            OF THEIR DAUGHTERS";
+        let cipher = SubstitutionCipher::default();
+        let secret = cipher.generate_key();
+        let cipher_text = encrypt(message, &secret).unwrap();
+
+        let candidates = crack(&cipher_text);
+        assert_eq!(candidates[0].2, message);
+        assert_eq!(
+            decrypt(&cipher_text, &candidates[0].0).unwrap(),
+            candidates[0].2
+        );
+        assert!(candidates.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        assert_eq!(crack(""), vec![]);
+    }
+}
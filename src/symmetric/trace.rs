@@ -0,0 +1,175 @@
+/*
+    Round by round trace of a block cipher.
+
+    Every intermediate value of a block (state, round keys, halves) is recorded as a step named
+    like in FIPS-197 appendix C, "round[ 1].s_box" is the state after the S-boxes of the first round.
+    The values are hex encoded, so the trace can be compared with the examples of the standards.
+
+    A message encrypted in CBC mode has one trace per block, the input of each trace is the
+    plain text block already xored with the previous cipher block (or the IV).
+    Decryption traces go the other way: the input is the cipher block and the output still has to be
+    xored with the previous cipher block (or the IV) to give the plain text block.
+*/
+
+use crate::error::CipherError;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for TraceFormat {
+    type Err = CipherError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(CipherError::InvalidOption {
+                option: "trace format",
+                value: s.to_string(),
+                expected: "text or json",
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub round: usize,
+    pub name: &'static str,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    pub cipher: &'static str,
+    // Position of the block in the message.
+    pub block: usize,
+    pub steps: Vec<Step>,
+}
+
+impl Trace {
+    pub fn new(cipher: &'static str, block: usize) -> Self {
+        Self {
+            cipher,
+            block,
+            steps: vec![],
+        }
+    }
+
+    pub fn record(&mut self, round: usize, name: &'static str, value: &[u8]) {
+        self.steps.push(Step {
+            round,
+            name,
+            value: hex::encode(value),
+        });
+    }
+
+    // Records the lowest `bits` of the value, DES halves and sub-keys don't fill a u64.
+    pub fn record_bits(&mut self, round: usize, name: &'static str, value: u64, bits: usize) {
+        self.steps.push(Step {
+            round,
+            name,
+            value: format!("{:0width$x}", value, width = bits / 4),
+        });
+    }
+
+    // The value of the first step with the given round and name.
+    #[allow(dead_code)]
+    pub fn value(&self, round: usize, name: &str) -> Option<&str> {
+        self.steps
+            .iter()
+            .find(|step| step.round == round && step.name == name)
+            .map(|step| step.value.as_str())
+    }
+
+    // Names are static identifiers and values are hex, nothing needs to be escaped.
+    pub fn to_json(&self) -> String {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| {
+                format!(
+                    "{{\"round\":{},\"step\":\"{}\",\"value\":\"{}\"}}",
+                    step.round, step.name, step.value
+                )
+            })
+            .collect::<Vec<String>>();
+        format!(
+            "{{\"cipher\":\"{}\",\"block\":{},\"steps\":[{}]}}",
+            self.cipher,
+            self.block,
+            steps.join(",")
+        )
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} block {}", self.cipher, self.block)?;
+        for step in &self.steps {
+            writeln!(
+                f,
+                "round[{:>2}].{:<8} {}",
+                step.round, step.name, step.value
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn render(traces: &[Trace], format: TraceFormat) -> String {
+    match format {
+        TraceFormat::Text => traces
+            .iter()
+            .map(Trace::to_string)
+            .collect::<Vec<String>>()
+            .join("\n"),
+        TraceFormat::Json => format!(
+            "[{}]",
+            traces
+                .iter()
+                .map(Trace::to_json)
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_trace() {
+        let mut trace = Trace::new("DES", 0);
+        trace.record(0, "input", &[0x01, 0x23]);
+        trace.record_bits(1, "left", 0xABC, 32);
+
+        assert_eq!(trace.value(1, "left"), Some("00000abc"));
+        assert_eq!(trace.value(2, "left"), None);
+        assert_eq!(
+            render(&[trace.clone()], TraceFormat::Text),
+            "DES block 0\nround[ 0].input    0123\nround[ 1].left     00000abc\n"
+        );
+        assert_eq!(
+            render(&[trace], TraceFormat::Json),
+            "[{\"cipher\":\"DES\",\"block\":0,\"steps\":[{\"round\":0,\"step\":\"input\",\"value\":\"0123\"},\
+             {\"round\":1,\"step\":\"left\",\"value\":\"00000abc\"}]}]"
+        );
+        assert_eq!("JSON".parse(), Ok(TraceFormat::Json));
+        assert_eq!(
+            "xml".parse::<TraceFormat>(),
+            Err(CipherError::InvalidOption {
+                option: "trace format",
+                value: "xml".to_string(),
+                expected: "text or json",
+            })
+        );
+    }
+}
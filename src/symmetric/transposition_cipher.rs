@@ -0,0 +1,428 @@
+use crate::analysis::fitness::{self, Bigrams, Quadgrams};
+use crate::analysis::{self, Language};
+use crate::cipher::{to_text, Cipher};
+use crate::error::CipherError;
+use crate::symmetric::alphabet::Alphabet;
+
+/*
+    Transposition ciphers don't change the symbols of the text, they change their order.
+    Every character is moved, spaces and punctuation included.
+
+    Rail fence
+    The text is written in a zigzag over N rails and read rail by rail.
+
+    secret: 3, plain text: WE ARE DISCOVERED
+
+    W . . . R . . . I . . . V . . . D
+    . E . A . E . D . S . O . E . E .
+    . . _ . . . _ . . . C . . . R . .
+
+    cipher text: WRIVDEAEDSOEE_ _CR (`_` being the spaces)
+
+    Columnar transposition
+    The text is written row by row under the letters of a keyword and the columns are read
+    in the alphabetical order of the keyword letters (repeated letters from left to right),
+    the last row may be incomplete.
+
+    secret: ZEBRA, plain text: WE ARE DISCOVERED
+
+    Z E B R A
+    4 2 1 3 0
+    ---------
+    W E _ A R
+    E _ D I S
+    C O V E R
+    E D
+
+    cipher text: RSR_DVE_ODAIEWECE
+
+    Double columnar transposition applies it twice with two keywords, which was used by both sides
+    in the second world war, it is much harder to break than the single one.
+*/
+
+const MAX_RAILS: usize = 20;
+const MAX_COLUMNS: usize = 10;
+// Every column order is tried up to this number of columns, above it the order is found by hill climbing.
+const EXHAUSTIVE_COLUMNS: usize = 6;
+const RESTARTS: usize = 10;
+
+// Rail of each position of a text written in zigzag.
+fn rails(length: usize, rails: usize) -> Vec<usize> {
+    let cycle = (2 * rails).saturating_sub(2).max(1);
+    (0..length)
+        .map(|i| {
+            let rail = i % cycle;
+            if rail < rails {
+                rail
+            } else {
+                cycle - rail
+            }
+        })
+        .collect()
+}
+
+// Positions of the plain text in the order they are read by the rail fence.
+fn rail_fence_order(length: usize, secret: usize) -> Vec<usize> {
+    let rails = rails(length, secret);
+    let mut order = (0..length).collect::<Vec<usize>>();
+    order.sort_by_key(|i| rails[*i]);
+    order
+}
+
+fn check_rails(secret: usize) -> Result<usize, CipherError> {
+    if secret == 0 {
+        return Err(CipherError::InvalidKey("there must be at least one rail"));
+    }
+    Ok(secret)
+}
+
+// Each position of the cipher text comes from the plain text position in `order`.
+fn encrypt_order(text: &str, order: &[usize]) -> String {
+    let chars = text.chars().collect::<Vec<char>>();
+    order.iter().map(|i| chars[*i]).collect()
+}
+
+fn decrypt_order(text: &str, order: &[usize]) -> String {
+    let mut chars = vec![' '; order.len()];
+    for (c, i) in text.chars().zip(order) {
+        chars[*i] = c;
+    }
+    chars.into_iter().collect()
+}
+
+pub fn rail_fence_encrypt(plain_text: &str, secret: usize) -> Result<String, CipherError> {
+    let order = rail_fence_order(plain_text.chars().count(), check_rails(secret)?);
+    Ok(encrypt_order(plain_text, &order))
+}
+
+pub fn rail_fence_decrypt(cipher_text: &str, secret: usize) -> Result<String, CipherError> {
+    let order = rail_fence_order(cipher_text.chars().count(), check_rails(secret)?);
+    Ok(decrypt_order(cipher_text, &order))
+}
+
+// Columns in the order they are read, sorted by the letters of the keyword.
+fn column_order(secret: &str) -> Result<Vec<usize>, CipherError> {
+    let letters = secret.to_uppercase().chars().collect::<Vec<char>>();
+    if letters.is_empty() {
+        return Err(CipherError::InvalidKey("secret must not be empty"));
+    }
+
+    let mut order = (0..letters.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|i| letters[*i]);
+    Ok(order)
+}
+
+// Positions of the plain text in the order they are read by the columnar transposition.
+fn columnar_order(length: usize, columns: &[usize]) -> Vec<usize> {
+    columns
+        .iter()
+        .flat_map(|column| (*column..length).step_by(columns.len()))
+        .collect()
+}
+
+pub fn columnar_encrypt(plain_text: &str, secret: &str) -> Result<String, CipherError> {
+    let order = columnar_order(plain_text.chars().count(), &column_order(secret)?);
+    Ok(encrypt_order(plain_text, &order))
+}
+
+pub fn columnar_decrypt(cipher_text: &str, secret: &str) -> Result<String, CipherError> {
+    let order = columnar_order(cipher_text.chars().count(), &column_order(secret)?);
+    Ok(decrypt_order(cipher_text, &order))
+}
+
+// The secrets are applied in order, two secrets make a double columnar transposition.
+pub fn double_columnar_encrypt(
+    plain_text: &str,
+    secrets: &[String],
+) -> Result<String, CipherError> {
+    secrets
+        .iter()
+        .try_fold(plain_text.to_string(), |text, secret| {
+            columnar_encrypt(&text, secret)
+        })
+}
+
+pub fn double_columnar_decrypt(
+    cipher_text: &str,
+    secrets: &[String],
+) -> Result<String, CipherError> {
+    secrets
+        .iter()
+        .rev()
+        .try_fold(cipher_text.to_string(), |text, secret| {
+            columnar_decrypt(&text, secret)
+        })
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RailFence;
+
+impl Cipher for RailFence {
+    type Key = usize;
+
+    fn encrypt(&self, key: &usize, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        rail_fence_encrypt(to_text(plain_text)?, *key).map(String::into_bytes)
+    }
+
+    fn decrypt(&self, key: &usize, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        rail_fence_decrypt(to_text(cipher_text)?, *key).map(String::into_bytes)
+    }
+}
+
+// One secret for a single columnar transposition, two for a double one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ColumnarTransposition;
+
+impl Cipher for ColumnarTransposition {
+    type Key = Vec<String>;
+
+    fn encrypt(&self, key: &Vec<String>, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        double_columnar_encrypt(to_text(plain_text)?, key).map(String::into_bytes)
+    }
+
+    fn decrypt(&self, key: &Vec<String>, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        double_columnar_decrypt(to_text(cipher_text)?, key).map(String::into_bytes)
+    }
+}
+
+/*
+    Breaking transposition ciphers
+
+    Rail fence: there are only a few possible numbers of rails, so every one of them is tried
+    and the candidates are ranked with english quadgrams.
+
+    Columnar transposition: for each number of columns we look for the order of the columns whose
+    decrypted text has the most english bigrams, letters that end up next to each other
+    in the plain text come from neighbor columns, so bigrams are enough to put the columns in order.
+    Up to 6 columns every order is tried, above that the order is found by hill climbing, swapping two columns
+    while the score improves. The best order of each number of columns is then ranked with quadgrams.
+    Double columnar transposition is not cracked here, its two secrets hide the neighbor columns.
+
+    Detecting transposition
+    A transposition keeps every symbol of the text, so the symbol frequencies are still the english ones,
+    while a substitution moves them to other symbols. If the chi-squared against english is low enough
+    the text was most likely transposed.
+*/
+
+// Uppercase symbols of every character, anything outside of the alphabet counts as a space.
+fn symbols(text: &str) -> Vec<usize> {
+    let alphabet = Alphabet::uppercase();
+    text.chars()
+        .map(|c| alphabet.position(c).unwrap_or(0))
+        .collect()
+}
+
+// Returns (rails, score, plain text) candidates, the most likely one first.
+pub fn crack_rail_fence(cipher_text: &str) -> Vec<(usize, f64, String)> {
+    let length = cipher_text.chars().count();
+    let candidates = (2..=MAX_RAILS.min(length.saturating_sub(1)))
+        .filter_map(|secret| Some((secret, rail_fence_decrypt(cipher_text, secret).ok()?)));
+    fitness::rank(candidates, Quadgrams::english())
+}
+
+fn score_order(cipher: &[usize], columns: &[usize], plain: &mut [usize]) -> f64 {
+    for (symbol, i) in cipher.iter().zip(columnar_order(cipher.len(), columns)) {
+        plain[i] = *symbol;
+    }
+    Bigrams::english().score_symbols(plain)
+}
+
+// Every permutation of the columns, in lexicographic order.
+fn permutations(columns: usize) -> Vec<Vec<usize>> {
+    if columns == 0 {
+        return vec![vec![]];
+    }
+
+    permutations(columns - 1)
+        .into_iter()
+        .flat_map(|permutation| {
+            (0..columns).map(move |position| {
+                let mut result = permutation.clone();
+                result.insert(position, columns - 1);
+                result
+            })
+        })
+        .collect()
+}
+
+fn climb_order(cipher: &[usize], columns: usize) -> Vec<usize> {
+    let mut plain = vec![0; cipher.len()];
+    let mut best = (0..columns).collect::<Vec<usize>>();
+    let mut best_score = score_order(cipher, &best, &mut plain);
+
+    for restart in 0..RESTARTS {
+        let mut order = best.clone();
+        order.rotate_left(restart % columns);
+        let mut score = score_order(cipher, &order, &mut plain);
+
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for i in 0..columns {
+                for j in i + 1..columns {
+                    order.swap(i, j);
+                    let candidate = score_order(cipher, &order, &mut plain);
+                    if candidate > score {
+                        score = candidate;
+                        improved = true;
+                    } else {
+                        order.swap(i, j);
+                    }
+                }
+            }
+        }
+
+        if score > best_score {
+            best_score = score;
+            best = order;
+        }
+    }
+
+    best
+}
+
+// Keyword with the same column order, A for the first column read, B for the second and so on.
+fn order_to_secret(columns: &[usize]) -> String {
+    let mut letters = vec!['A'; columns.len()];
+    for (rank, column) in columns.iter().enumerate() {
+        letters[*column] = (b'A' + rank as u8) as char;
+    }
+    letters.into_iter().collect()
+}
+
+// Returns (secret, score, plain text) candidates, the most likely one first.
+pub fn crack_columnar(cipher_text: &str) -> Vec<(String, f64, String)> {
+    let cipher = symbols(cipher_text);
+    let candidates = (2..=MAX_COLUMNS.min(cipher.len() / 2))
+        .map(|columns| {
+            let order = if columns <= EXHAUSTIVE_COLUMNS {
+                let mut plain = vec![0; cipher.len()];
+                permutations(columns)
+                    .into_iter()
+                    .map(|order| (score_order(&cipher, &order, &mut plain), order))
+                    .max_by(|a, b| a.0.total_cmp(&b.0))
+                    .map(|(_, order)| order)
+                    .unwrap_or_default()
+            } else {
+                climb_order(&cipher, columns)
+            };
+
+            let secret = order_to_secret(&order);
+            let plain_text = decrypt_order(cipher_text, &columnar_order(cipher.len(), &order));
+            (secret, plain_text)
+        })
+        .collect::<Vec<(String, String)>>();
+
+    fitness::rank(candidates, Quadgrams::english())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Family {
+    Transposition,
+    Substitution,
+}
+
+// A transposition keeps the english frequencies, substitutions go way above the threshold.
+pub fn detect(cipher_text: &str) -> Family {
+    if analysis::chi_squared_per_symbol(cipher_text, Language::English)
+        < analysis::ENGLISH_CHI_SQUARED_PER_SYMBOL
+    {
+        Family::Transposition
+    } else {
+        Family::Substitution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symmetric::{substitution_cipher, vigenere_cipher};
+
+    const MESSAGE: &str =
+        "IT IS A TRUTH UNIVERSALLY ACKNOWLEDGED THAT A SINGLE MAN IN POSSESSION OF A GOOD \
+        FORTUNE MUST BE IN WANT OF A WIFE HOWEVER LITTLE KNOWN THE FEELINGS OR VIEWS OF SUCH A MAN \
+        MAY BE ON HIS FIRST ENTERING A NEIGHBOURHOOD";
+
+    #[test]
+    fn rail_fence() {
+        let result = rail_fence_encrypt("WE ARE DISCOVERED", 3).unwrap();
+        assert_eq!(result, "WRIVDEAEDSOEE  CR");
+        assert_eq!(rail_fence_decrypt(&result, 3).unwrap(), "WE ARE DISCOVERED");
+
+        assert_eq!(rail_fence_encrypt("HELLO", 1).unwrap(), "HELLO");
+        assert_eq!(rail_fence_encrypt("HELLO", 10).unwrap(), "HELLO");
+        assert_eq!(
+            rail_fence_encrypt("HELLO", 0),
+            Err(CipherError::InvalidKey("there must be at least one rail"))
+        );
+
+        let cipher_text = rail_fence_encrypt(MESSAGE, 7).unwrap();
+        let result = crack_rail_fence(&cipher_text);
+        assert_eq!(result[0].0, 7);
+        assert_eq!(result[0].2, MESSAGE);
+    }
+
+    #[test]
+    fn columnar() {
+        let result = columnar_encrypt("WE ARE DISCOVERED", "ZEBRA").unwrap();
+        assert_eq!(result, "RSR DVE ODAIEWECE");
+        assert_eq!(
+            columnar_decrypt(&result, "zebra").unwrap(),
+            "WE ARE DISCOVERED"
+        );
+
+        let secrets = vec!["ZEBRA".to_string(), "STRIPE".to_string()];
+        let cipher = ColumnarTransposition;
+        let encrypted = cipher.encrypt(&secrets, b"WE ARE DISCOVERED").unwrap();
+        assert_ne!(
+            encrypted,
+            columnar_encrypt("WE ARE DISCOVERED", "ZEBRA")
+                .unwrap()
+                .into_bytes()
+        );
+        assert_eq!(
+            cipher.decrypt(&secrets, &encrypted).unwrap(),
+            b"WE ARE DISCOVERED"
+        );
+
+        assert_eq!(
+            columnar_encrypt("HELLO", ""),
+            Err(CipherError::InvalidKey("secret must not be empty"))
+        );
+    }
+
+    #[test]
+    fn crack_columnar_transposition() {
+        for secret in ["ZEBRA", "GERMAN", "PLANETS"] {
+            let cipher_text = columnar_encrypt(MESSAGE, secret).unwrap();
+            let result = crack_columnar(&cipher_text);
+            assert_eq!(result[0].2, MESSAGE);
+            assert_eq!(
+                columnar_encrypt(MESSAGE, &result[0].0).unwrap(),
+                cipher_text
+            );
+        }
+    }
+
+    #[test]
+    fn detect_family() {
+        assert_eq!(
+            detect(&rail_fence_encrypt(MESSAGE, 5).unwrap()),
+            Family::Transposition
+        );
+        assert_eq!(
+            detect(&columnar_encrypt(MESSAGE, "ZEBRA").unwrap()),
+            Family::Transposition
+        );
+        assert_eq!(
+            detect(&substitution_cipher::encrypt(MESSAGE, "ZEBRAS").unwrap()),
+            Family::Substitution
+        );
+        assert_eq!(
+            detect(&vigenere_cipher::encrypt(MESSAGE, "LEMON").unwrap()),
+            Family::Substitution
+        );
+        // nothing to measure, so it can't be taken for english
+        assert_eq!(detect("123"), Family::Substitution);
+    }
+}
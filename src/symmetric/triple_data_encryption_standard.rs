@@ -0,0 +1,285 @@
+/*
+    Triple DES (TDEA)
+
+    DES keys became too short to resist brute force, so the same cipher is applied three times
+    with encrypt - decrypt - encrypt (EDE):
+
+    cipher = E(K3, D(K2, E(K1, plain)))
+    plain  = D(K1, E(K2, D(K3, cipher)))
+
+    Keying options
+    -> EDE3: three independent keys, 24 bytes (168 bits used).
+    -> EDE2: two keys, 16 bytes, K3 is K1 again (112 bits used).
+
+    Decrypting in the middle is what makes it compatible with single DES: with K1 = K2 = K3 the first two
+    steps cancel out and only E(K3) is left. The same happens when K1 = K2 (only E(K3) is left) or K2 = K3
+    (only E(K1) is left), so those keys are rejected, they would silently give single DES.
+    The parity bits (last bit of each byte) aren't used by DES, so keys differing only there are equal.
+    Each key is also checked against the weak and semi-weak keys of DES.
+
+    Long messages use CBC with PKCS#7 padding, the same modes used by single DES.
+*/
+
+use crate::cipher::{BlockCipher, Cipher, KeyGen};
+use crate::error::CipherError;
+use crate::symmetric::data_encryption_standard::{
+    self as des, cbc_decrypt, cbc_encrypt, check_iv, check_weakness, fix_parity, process_block,
+    sub_keys, to_u64, BLOCK_LENGTH, KEY_BITS, ROUNDS,
+};
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyingOption {
+    Ede3,
+    Ede2,
+}
+
+impl KeyingOption {
+    pub fn key_length(&self) -> usize {
+        match self {
+            Self::Ede3 => 3 * des::KEY_LENGTH,
+            Self::Ede2 => 2 * des::KEY_LENGTH,
+        }
+    }
+}
+
+// The sub-keys of K1, K2 and K3.
+struct Keys([[u64; ROUNDS]; 3]);
+
+fn check_key(key: &[u8]) -> Result<(KeyingOption, Keys), CipherError> {
+    let option = match key.len() {
+        24 => KeyingOption::Ede3,
+        16 => KeyingOption::Ede2,
+        // the length of the closest keying option is reported
+        actual => {
+            let expected = if actual < KeyingOption::Ede3.key_length() {
+                KeyingOption::Ede2.key_length()
+            } else {
+                KeyingOption::Ede3.key_length()
+            };
+            return Err(CipherError::InvalidKeyLength { expected, actual });
+        }
+    };
+
+    let keys = key
+        .chunks(des::KEY_LENGTH)
+        .map(to_u64)
+        .collect::<Vec<u64>>();
+    let (k1, k2) = (keys[0], keys[1]);
+    let k3 = keys.get(2).copied().unwrap_or(k1);
+    for key in &keys {
+        check_weakness(*key)?;
+    }
+    if k1 & KEY_BITS == k2 & KEY_BITS || k2 & KEY_BITS == k3 & KEY_BITS {
+        return Err(CipherError::InvalidKey(
+            "equal consecutive keys make triple DES a single DES",
+        ));
+    }
+
+    Ok((option, Keys([sub_keys(k1), sub_keys(k2), sub_keys(k3)])))
+}
+
+fn encrypt_block(block: u64, keys: &Keys) -> u64 {
+    let [k1, k2, k3] = &keys.0;
+    process_block(
+        process_block(process_block(block, k1, false), k2, true),
+        k3,
+        false,
+    )
+}
+
+fn decrypt_block(block: u64, keys: &Keys) -> u64 {
+    let [k1, k2, k3] = &keys.0;
+    process_block(
+        process_block(process_block(block, k3, true), k2, false),
+        k1,
+        true,
+    )
+}
+
+// 3DES/CBC/PKCS#7, the result is hex encoded.
+#[allow(dead_code)]
+pub fn encrypt(data: &[u8], key: &[u8], iv: &[u8]) -> Result<String, CipherError> {
+    encrypt_bytes(data, key, iv).map(hex::encode)
+}
+
+#[allow(dead_code)]
+pub fn decrypt(encrypted_data: &str, key: &[u8], iv: &[u8]) -> Result<String, CipherError> {
+    let encrypted_data = hex::decode(encrypted_data).map_err(|_| CipherError::InvalidEncoding)?;
+    let decrypted_data = decrypt_bytes(&encrypted_data, key, iv)?;
+    String::from_utf8(decrypted_data).map_err(|_| CipherError::InvalidEncoding)
+}
+
+fn encrypt_bytes(data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, CipherError> {
+    let (_, keys) = check_key(key)?;
+    let iv = check_iv(iv)?;
+    Ok(cbc_encrypt(data, iv, |block| encrypt_block(block, &keys)))
+}
+
+fn decrypt_bytes(data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, CipherError> {
+    let (_, keys) = check_key(key)?;
+    let iv = check_iv(iv)?;
+    cbc_decrypt(data, iv, |block| decrypt_block(block, &keys))
+}
+
+pub struct TripleDes {
+    iv: [u8; BLOCK_LENGTH],
+}
+
+impl TripleDes {
+    pub fn new(iv: [u8; BLOCK_LENGTH]) -> Self {
+        Self { iv }
+    }
+}
+
+impl Cipher for TripleDes {
+    type Key = Vec<u8>;
+
+    fn encrypt(&self, key: &Vec<u8>, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        encrypt_bytes(plain_text, key, &self.iv)
+    }
+
+    fn decrypt(&self, key: &Vec<u8>, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        decrypt_bytes(cipher_text, key, &self.iv)
+    }
+}
+
+// A single block without chaining or padding.
+impl BlockCipher for TripleDes {
+    const BLOCK_SIZE: usize = BLOCK_LENGTH;
+
+    fn encrypt_block(&self, key: &Vec<u8>, block: &[u8]) -> Result<Vec<u8>, CipherError> {
+        let (_, keys) = check_key(key)?;
+        if block.len() != BLOCK_LENGTH {
+            return Err(CipherError::InvalidBlock);
+        }
+        Ok(encrypt_block(to_u64(block), &keys).to_be_bytes().to_vec())
+    }
+
+    fn decrypt_block(&self, key: &Vec<u8>, block: &[u8]) -> Result<Vec<u8>, CipherError> {
+        let (_, keys) = check_key(key)?;
+        if block.len() != BLOCK_LENGTH {
+            return Err(CipherError::InvalidBlock);
+        }
+        Ok(decrypt_block(to_u64(block), &keys).to_be_bytes().to_vec())
+    }
+}
+
+// Three independent keys with odd parity, generated again in the unlikely case of weak or equal consecutive keys.
+impl KeyGen for TripleDes {
+    fn generate_key(&self) -> Vec<u8> {
+        loop {
+            let key = (0..KeyingOption::Ede3.key_length() / des::KEY_LENGTH)
+                .flat_map(|_| fix_parity(rand::thread_rng().gen()).to_be_bytes())
+                .collect::<Vec<u8>>();
+            if check_key(&key).is_ok() {
+                return key;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EDE3_KEY: &str = "0123456789abcdef23456789abcdef01456789abcdef0123";
+    const IV: &[u8; 8] = b"00000000";
+
+    #[test]
+    fn known_answer_tests() {
+        // NIST SP 800-67 example, three keys and ECB
+        let cipher = TripleDes::new(*IV);
+        let key = hex::decode(EDE3_KEY).unwrap();
+        let plain_text = b"The qufck brown fox jump";
+        let encrypted = plain_text
+            .chunks(BLOCK_LENGTH)
+            .flat_map(|block| cipher.encrypt_block(&key, block).unwrap())
+            .collect::<Vec<u8>>();
+        assert_eq!(
+            hex::encode(&encrypted),
+            "a826fd8ce53b855fcce21c8112256fe668d5c05dd9b6b900"
+        );
+        let decrypted = encrypted
+            .chunks(BLOCK_LENGTH)
+            .flat_map(|block| cipher.decrypt_block(&key, block).unwrap())
+            .collect::<Vec<u8>>();
+        assert_eq!(decrypted, plain_text);
+    }
+
+    #[test]
+    fn interoperability() {
+        // openssl enc -des-ede3-cbc and -des-ede-cbc with the same key and iv
+        let key = hex::decode(EDE3_KEY).unwrap();
+        let encrypted = encrypt(b"Hello World", &key, IV).unwrap();
+        assert_eq!(encrypted, "1577fc223658d92159ced14ef7d1b72a");
+        assert_eq!(decrypt(&encrypted, &key, IV).unwrap(), "Hello World");
+
+        let key = &key[..16];
+        let encrypted = encrypt(b"Hello World", key, IV).unwrap();
+        assert_eq!(encrypted, "82c230daf619940446ecb41ffcef8175");
+        assert_eq!(decrypt(&encrypted, key, IV).unwrap(), "Hello World");
+        assert_eq!(check_key(key).unwrap().0, KeyingOption::Ede2);
+    }
+
+    #[test]
+    fn single_des_compatibility() {
+        // K1 = K2 = K3 is single DES, which is why it's rejected
+        let key = hex::decode("0123456789abcdef").unwrap();
+        let keys = Keys([sub_keys(to_u64(&key)); 3]);
+        assert_eq!(
+            encrypt_block(0x4E6F772069732074, &keys),
+            des::encrypt_block(0x4E6F772069732074, to_u64(&key))
+        );
+
+        let invalid =
+            CipherError::InvalidKey("equal consecutive keys make triple DES a single DES");
+        assert_eq!(encrypt(b"data", &key.repeat(3), IV), Err(invalid.clone()));
+        assert_eq!(encrypt(b"data", &key.repeat(2), IV), Err(invalid.clone()));
+
+        // keys differing only in the parity bits are the same key
+        let mut k2 = key.clone();
+        k2[0] ^= 1;
+        assert_eq!(
+            encrypt(
+                b"data",
+                &[key.clone(), k2, hex::decode("23456789abcdef01").unwrap()].concat(),
+                IV
+            ),
+            Err(invalid)
+        );
+
+        let weak = [
+            0xFEFEFEFEFEFEFEFEu64,
+            0x0123456789ABCDEF,
+            0x23456789ABCDEF01,
+        ]
+        .iter()
+        .flat_map(|k| k.to_be_bytes())
+        .collect::<Vec<u8>>();
+        assert_eq!(encrypt(b"data", &weak, IV), Err(CipherError::WeakKey));
+
+        assert_eq!(
+            encrypt(b"data", &key, IV),
+            Err(CipherError::InvalidKeyLength {
+                expected: 16,
+                actual: 8
+            })
+        );
+        assert_eq!(
+            encrypt(b"data", &key.repeat(4), IV),
+            Err(CipherError::InvalidKeyLength {
+                expected: 24,
+                actual: 32
+            })
+        );
+
+        let cipher = TripleDes::new(*IV);
+        let key = cipher.generate_key();
+        let encrypted = Cipher::encrypt(&cipher, &key, b"Hello World").unwrap();
+        assert_eq!(
+            Cipher::decrypt(&cipher, &key, &encrypted).unwrap(),
+            b"Hello World"
+        );
+    }
+}
@@ -5,44 +5,518 @@
     of the letters of the plain text and the secret.
 
     the secret will have a loop so that each letter of the plain text will have it's own 'secret' number to be swapped.
+
+    Variants (Mode)
+    -> Repeating: the classic one described above, cipher = plain + secret.
+    -> Autokey: the secret (primer) is used only once and then extended with the plain text itself,
+       so the key never repeats.
+       secret: KEY, plain text: ATTACK -> key: KEYATT
+    -> Beaufort: cipher = secret - plain, the same operation decrypts, cipher - plain gives the secret back.
+    -> Variant Beaufort: cipher = plain - secret, which is vigenere decryption used to encrypt.
+    -> Running key: the secret is a long text (a page of a book) used only once, it must have at least
+       as many symbols as the text, symbols of the secret outside of the alphabet are skipped.
 */
 
-use crate::symmetric::ALPHABET;
+use crate::analysis::fitness::{self, ChiSquared, Quadgrams};
+use crate::analysis::{self, Language};
+use crate::cipher::{to_text, Cipher, StreamCipher};
+use crate::error::CipherError;
+use crate::symmetric::alphabet::{Alphabet, UnknownSymbol};
+use crate::symmetric::{caesar_cipher, ALPHABET};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    #[default]
+    Repeating,
+    Autokey,
+    Beaufort,
+    VariantBeaufort,
+    RunningKey,
+}
+
+impl FromStr for Mode {
+    type Err = CipherError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "repeating" => Ok(Self::Repeating),
+            "autokey" => Ok(Self::Autokey),
+            "beaufort" => Ok(Self::Beaufort),
+            "variantbeaufort" => Ok(Self::VariantBeaufort),
+            "runningkey" => Ok(Self::RunningKey),
+            _ => Err(CipherError::InvalidOption {
+                option: "mode",
+                value: s.to_string(),
+                expected: "repeating, autokey, beaufort, variant-beaufort or running-key",
+            }),
+        }
+    }
+}
+
+fn map_secret(secret: &str, alphabet: &Alphabet) -> Result<Vec<usize>, CipherError> {
+    if secret.is_empty() {
+        return Err(CipherError::InvalidKey("secret must not be empty"));
+    }
 
-fn map_secret(secret: &str) -> Vec<usize> {
     secret
-        .to_uppercase()
         .chars()
-        .map(|c| ALPHABET.iter().position(|a| *a == c).unwrap_or_default())
-        .collect::<Vec<usize>>()
+        .map(|c| alphabet.position(c).ok_or(CipherError::InvalidKeySymbol(c)))
+        .collect::<Result<Vec<usize>, CipherError>>()
 }
 
-pub fn encrypt(plain_text: &str, secret: &str) -> String {
-    let mut result = String::new();
-    let secret = map_secret(secret);
-    for (index, char) in plain_text.to_uppercase().chars().enumerate() {
-        let secret_value = secret[index % secret.len()];
-        let plain_text_char_index = ALPHABET.iter().position(|a| *a == char).unwrap_or_default();
-        let index = (plain_text_char_index + secret_value) % ALPHABET.len();
-        result.push(ALPHABET[index]);
+// The running key is a free text, so its symbols outside of the alphabet are skipped instead of rejected.
+fn map_running_key(secret: &str, alphabet: &Alphabet) -> Result<Vec<usize>, CipherError> {
+    let secret = secret
+        .chars()
+        .filter_map(|c| alphabet.position(c))
+        .collect::<Vec<usize>>();
+    if secret.is_empty() {
+        return Err(CipherError::InvalidKey("secret must not be empty"));
     }
+    Ok(secret)
+}
 
-    result
+fn check_running_key(secret: &[usize], text: &str, alphabet: &Alphabet) -> Result<(), CipherError> {
+    let required = text
+        .chars()
+        .filter(|c| alphabet.position(*c).is_some())
+        .count();
+    if secret.len() < required {
+        return Err(CipherError::KeyTooShort {
+            required,
+            available: secret.len(),
+        });
+    }
+    Ok(())
+}
+
+fn shift(mode: Mode, symbol: usize, secret: usize, length: usize, decrypting: bool) -> usize {
+    let subtract = match mode {
+        Mode::Beaufort => return (length + secret - symbol) % length,
+        Mode::VariantBeaufort => !decrypting,
+        _ => decrypting,
+    };
+
+    if subtract {
+        (length + symbol - secret) % length
+    } else {
+        (symbol + secret) % length
+    }
+}
+
+fn transform_with(
+    text: &str,
+    secret: &str,
+    mode: Mode,
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+    decrypting: bool,
+) -> Result<String, CipherError> {
+    let mut secret = match mode {
+        Mode::RunningKey => {
+            let secret = map_running_key(secret, alphabet)?;
+            check_running_key(&secret, text, alphabet)?;
+            secret
+        }
+        _ => map_secret(secret, alphabet)?,
+    };
+    let primer = secret.len();
+    let mut secret_index = 0;
+
+    alphabet.transform(text, policy, |index| {
+        let secret_value = match mode {
+            Mode::Autokey | Mode::RunningKey => secret[secret_index],
+            _ => secret[secret_index % primer],
+        };
+        secret_index += 1;
+
+        let result = shift(mode, index, secret_value, alphabet.len(), decrypting);
+        // the autokey is extended with the plain text, which is the result when decrypting
+        if mode == Mode::Autokey {
+            secret.push(if decrypting { result } else { index });
+        }
+        result
+    })
+}
+
+#[allow(dead_code)]
+pub fn encrypt(plain_text: &str, secret: &str) -> Result<String, CipherError> {
+    encrypt_with(
+        plain_text,
+        secret,
+        &Alphabet::default(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+pub fn decrypt(cipher_text: &str, secret: &str) -> Result<String, CipherError> {
+    decrypt_with(
+        cipher_text,
+        secret,
+        &Alphabet::default(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+// Traditional vigenere, only letters are shifted (keeping their case) and advance the secret.
+#[allow(dead_code)]
+pub fn encrypt_preserving_case(plain_text: &str, secret: &str) -> Result<String, CipherError> {
+    encrypt_with(
+        plain_text,
+        secret,
+        &Alphabet::letters(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+#[allow(dead_code)]
+pub fn decrypt_preserving_case(cipher_text: &str, secret: &str) -> Result<String, CipherError> {
+    decrypt_with(
+        cipher_text,
+        secret,
+        &Alphabet::letters(),
+        UnknownSymbol::PassThrough,
+    )
+}
+
+// The secret only advances on symbols of the alphabet.
+pub fn encrypt_with(
+    plain_text: &str,
+    secret: &str,
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    encrypt_mode_with(plain_text, secret, Mode::Repeating, alphabet, policy)
+}
+
+pub fn decrypt_with(
+    cipher_text: &str,
+    secret: &str,
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    decrypt_mode_with(cipher_text, secret, Mode::Repeating, alphabet, policy)
 }
 
-pub fn decrypt(cipher_text: &str, secret: &str) -> String {
-    let mut result = String::new();
-    let secret = map_secret(secret);
-    for (index, char) in cipher_text.to_uppercase().chars().enumerate() {
-        let secret_value = secret[index % secret.len()];
-        let cipher_text_char_index = ALPHABET.iter().position(|a| *a == char).unwrap_or_default();
-        let index = (ALPHABET.len() + cipher_text_char_index - secret_value) % ALPHABET.len();
-        result.push(ALPHABET[index]);
+pub fn encrypt_mode_with(
+    plain_text: &str,
+    secret: &str,
+    mode: Mode,
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    transform_with(plain_text, secret, mode, alphabet, policy, false)
+}
+
+pub fn decrypt_mode_with(
+    cipher_text: &str,
+    secret: &str,
+    mode: Mode,
+    alphabet: &Alphabet,
+    policy: UnknownSymbol,
+) -> Result<String, CipherError> {
+    transform_with(cipher_text, secret, mode, alphabet, policy, true)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VigenereCipher {
+    pub alphabet: Alphabet,
+    pub policy: UnknownSymbol,
+    pub mode: Mode,
+}
+
+impl VigenereCipher {
+    pub fn new(alphabet: Alphabet, policy: UnknownSymbol) -> Self {
+        Self {
+            alphabet,
+            policy,
+            mode: Mode::default(),
+        }
+    }
+
+    pub fn with_mode(self, mode: Mode) -> Self {
+        Self { mode, ..self }
+    }
+}
+
+impl Cipher for VigenereCipher {
+    type Key = String;
+
+    fn encrypt(&self, key: &String, plain_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        encrypt_mode_with(
+            to_text(plain_text)?,
+            key,
+            self.mode,
+            &self.alphabet,
+            self.policy,
+        )
+        .map(String::into_bytes)
+    }
+
+    fn decrypt(&self, key: &String, cipher_text: &[u8]) -> Result<Vec<u8>, CipherError> {
+        decrypt_mode_with(
+            to_text(cipher_text)?,
+            key,
+            self.mode,
+            &self.alphabet,
+            self.policy,
+        )
+        .map(String::into_bytes)
+    }
+}
+
+// Beaufort subtracts the plain text and autokey depends on it, so neither of them is a key stream of shifts.
+impl StreamCipher for VigenereCipher {
+    fn key_stream(&self, key: &String, length: usize) -> Result<Vec<usize>, CipherError> {
+        match self.mode {
+            Mode::Repeating => {
+                let secret = map_secret(key, &self.alphabet)?;
+                Ok(secret.into_iter().cycle().take(length).collect())
+            }
+            Mode::VariantBeaufort => {
+                let secret = map_secret(key, &self.alphabet)?;
+                let alphabet_length = self.alphabet.len();
+                Ok(secret
+                    .into_iter()
+                    .map(|s| (alphabet_length - s) % alphabet_length)
+                    .cycle()
+                    .take(length)
+                    .collect())
+            }
+            Mode::RunningKey => {
+                let secret = map_running_key(key, &self.alphabet)?;
+                if secret.len() < length {
+                    return Err(CipherError::KeyTooShort {
+                        required: length,
+                        available: secret.len(),
+                    });
+                }
+                Ok(secret.into_iter().take(length).collect())
+            }
+            Mode::Autokey | Mode::Beaufort => Err(CipherError::InvalidKey(
+                "the key of this mode isn't a stream of shifts",
+            )),
+        }
+    }
+}
+
+/*
+    Breaking vigenere cipher
+
+    Once we know the length of the secret, every N-th letter of the cipher text was shifted by the same
+    letter of the secret, so each one of those columns is just a caesar cipher that can be solved
+    by comparing the letter frequencies of each possible shift against the english frequencies (chi-squared).
+
+    To estimate the length of the secret we use two techniques:
+
+    Kasiski examination: repeated trigrams in the cipher text are usually the same plain text
+    encrypted with the same part of the secret, so the distance between them tends to be a multiple of the secret length.
+
+    Index of coincidence (Friedman test): the probability of two random letters of a text being equal.
+    English text has a much higher index than random text, so when the cipher text is split in columns
+    using the right secret length, every column will look like english again.
+*/
+
+const MAX_SECRET_LENGTH: usize = 20;
+const SECRET_LENGTH_CANDIDATES: usize = 5;
+
+// Symbols outside the alphabet are not encrypted and don't advance the secret, so they are skipped.
+fn to_symbols(text: &str) -> Vec<usize> {
+    analysis::symbols(text, &Alphabet::uppercase())
+}
+
+fn column(symbols: &[usize], secret_length: usize, offset: usize) -> Vec<usize> {
+    symbols
+        .iter()
+        .skip(offset)
+        .step_by(secret_length)
+        .copied()
+        .collect()
+}
+
+// Fraction of the distances between repeated trigrams that are a multiple of each secret length.
+fn kasiski_examination(symbols: &[usize], max_length: usize) -> Vec<f64> {
+    let mut positions: HashMap<&[usize], Vec<usize>> = HashMap::new();
+    for (i, trigram) in symbols.windows(3).enumerate() {
+        positions.entry(trigram).or_default().push(i);
+    }
+
+    let distances = positions
+        .values()
+        .flat_map(|p| p.windows(2).map(|w| w[1] - w[0]))
+        .collect::<Vec<usize>>();
+
+    let mut result = vec![0.0; max_length + 1];
+    if distances.is_empty() {
+        return result;
+    }
+
+    for (length, score) in result.iter_mut().enumerate().skip(1) {
+        let multiples = distances.iter().filter(|d| *d % length == 0).count();
+        *score = multiples as f64 / distances.len() as f64;
     }
 
     result
 }
 
+// Secret lengths ordered from the most to the least likely one.
+pub fn estimate_secret_length(symbols: &[usize]) -> Vec<usize> {
+    let max_length = MAX_SECRET_LENGTH.min(symbols.len() / 2).max(1);
+    let english_ioc = Language::English.index_of_coincidence();
+    let kasiski = kasiski_examination(symbols, max_length);
+
+    let mut scores = (1..=max_length)
+        .map(|length| {
+            let ioc = analysis::periodic_coincidence(symbols, ALPHABET.len(), length);
+            (length, ioc / english_ioc * (1.0 + kasiski[length]))
+        })
+        .collect::<Vec<(usize, f64)>>();
+
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scores.into_iter().map(|(length, _)| length).collect()
+}
+
+// Each column is a caesar cipher, so the best scored caesar secret is the secret letter for the column,
+// the symbols of a column aren't next to each other in the text, so only their frequencies matter.
+fn solve_column(symbols: &[usize]) -> usize {
+    let column = symbols.iter().map(|&s| ALPHABET[s]).collect::<String>();
+    caesar_cipher::crack_with(&column, &ChiSquared(Language::English))
+        .first()
+        .map(|(secret, _, _)| *secret)
+        .unwrap_or_default()
+}
+
+// Returns (secret, score, plain text) candidates, the most likely one first.
+pub fn crack(cipher_text: &str) -> Vec<(String, f64, String)> {
+    let symbols = to_symbols(cipher_text);
+    if symbols.is_empty() {
+        return vec![];
+    }
+
+    let mut candidates = estimate_secret_length(&symbols)
+        .into_iter()
+        .take(SECRET_LENGTH_CANDIDATES)
+        .filter_map(|length| {
+            let secret = (0..length)
+                .map(|offset| ALPHABET[solve_column(&column(&symbols, length, offset))])
+                .collect::<String>();
+            let plain_text = decrypt(cipher_text, &secret).ok()?;
+            let score = fitness::score(&plain_text);
+            Some((secret, score, plain_text))
+        })
+        .collect::<Vec<(String, f64, String)>>();
+
+    // a secret repeated twice decrypts the same text, the shortest one is kept
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.len().cmp(&b.0.len())));
+    candidates.dedup_by(|a, b| a.2 == b.2);
+    candidates
+}
+
+/*
+    Breaking autokey
+
+    After the primer, the key is the plain text itself, so the cipher text doesn't repeat and the
+    index of coincidence can't find a period. But once the primer length is guessed, every primer letter
+    decrypts a whole chain of the text: the plain symbol at i is the cipher symbol at i minus the plain symbol
+    at i - primer length, so each primer letter only affects every N-th symbol, just like a vigenere column.
+
+    For each primer length every letter of each column is tried and the one whose chain has the most english
+    letter frequencies is kept, then the primer is improved letter by letter using quadgrams over the whole text,
+    and finally the best primer of each length is ranked.
+*/
+
+const MAX_PRIMER_LENGTH: usize = 15;
+const PRIMER_ROUNDS: usize = 5;
+
+fn autokey_decrypt_symbols(symbols: &[usize], primer: &[usize]) -> Vec<usize> {
+    let mut plain: Vec<usize> = Vec::with_capacity(symbols.len());
+    for (i, symbol) in symbols.iter().enumerate() {
+        let secret = if i < primer.len() {
+            primer[i]
+        } else {
+            plain[i - primer.len()]
+        };
+        plain.push((ALPHABET.len() + symbol - secret) % ALPHABET.len());
+    }
+    plain
+}
+
+// Primer letter that gives the most english looking chain of symbols for the column.
+fn solve_autokey_column(symbols: &[usize], primer_length: usize, offset: usize) -> usize {
+    let candidates = (0..ALPHABET.len()).map(|secret| {
+        let mut previous = secret;
+        let chain = column(symbols, primer_length, offset)
+            .into_iter()
+            .map(|symbol| {
+                previous = (ALPHABET.len() + symbol - previous) % ALPHABET.len();
+                ALPHABET[previous]
+            })
+            .collect::<String>();
+        (secret, chain)
+    });
+
+    fitness::rank(candidates, &ChiSquared(Language::English))
+        .first()
+        .map(|(secret, _, _)| *secret)
+        .unwrap_or_default()
+}
+
+fn improve_primer(symbols: &[usize], mut primer: Vec<usize>) -> Vec<usize> {
+    let quadgrams = Quadgrams::english();
+    let mut best = quadgrams.score_symbols(&autokey_decrypt_symbols(symbols, &primer));
+
+    for _ in 0..PRIMER_ROUNDS {
+        let mut improved = false;
+        for position in 0..primer.len() {
+            let mut current = primer[position];
+            for secret in 0..ALPHABET.len() {
+                primer[position] = secret;
+                let score = quadgrams.score_symbols(&autokey_decrypt_symbols(symbols, &primer));
+                if score > best {
+                    best = score;
+                    current = secret;
+                    improved = true;
+                }
+            }
+            primer[position] = current;
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    primer
+}
+
+// Returns (primer, score, plain text) candidates, the most likely one first.
+pub fn crack_autokey(cipher_text: &str) -> Vec<(String, f64, String)> {
+    let symbols = to_symbols(cipher_text);
+    let candidates = (1..=MAX_PRIMER_LENGTH.min(symbols.len() / 2))
+        .filter_map(|length| {
+            let primer = (0..length)
+                .map(|offset| solve_autokey_column(&symbols, length, offset))
+                .collect::<Vec<usize>>();
+            let primer = improve_primer(&symbols, primer)
+                .into_iter()
+                .map(|s| ALPHABET[s])
+                .collect::<String>();
+            let plain_text = decrypt_mode_with(
+                cipher_text,
+                &primer,
+                Mode::Autokey,
+                &Alphabet::default(),
+                UnknownSymbol::PassThrough,
+            )
+            .ok()?;
+            Some((primer, plain_text))
+        })
+        .collect::<Vec<(String, String)>>();
+
+    fitness::rank(candidates, Quadgrams::english())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,10 +524,180 @@ mod tests {
     fn encrypt_decrypt_message() {
         let message = "Hello World";
         let secret = "secret";
-        let result = encrypt(message, secret);
+        let result = encrypt(message, secret).unwrap();
         assert_eq!(result, " JOCTTOTUCI");
 
-        let decrypted = decrypt(&result, secret);
+        let decrypted = decrypt(&result, secret).unwrap();
         assert_eq!(decrypted, "HELLO WORLD");
     }
+
+    #[test]
+    fn reject_invalid_secret() {
+        assert_eq!(
+            encrypt("Hello World", ""),
+            Err(CipherError::InvalidKey("secret must not be empty"))
+        );
+        assert_eq!(
+            decrypt("Hello World", "s3cret"),
+            Err(CipherError::InvalidKeySymbol('3'))
+        );
+    }
+
+    #[test]
+    fn encrypt_decrypt_with_alphabet() {
+        let message = "Hello, World! 42";
+        let secret = "secret";
+        let result = encrypt(message, secret).unwrap();
+        assert_eq!(result, " JOCT,TOTUCI!T42");
+        assert_eq!(decrypt(&result, secret).unwrap(), "HELLO, WORLD! 42");
+
+        let alphabet = Alphabet::printable_ascii();
+        let result = encrypt_with(message, secret, &alphabet, UnknownSymbol::Reject).unwrap();
+        let decrypted = decrypt_with(&result, secret, &alphabet, UnknownSymbol::Reject).unwrap();
+        assert_eq!(decrypted, message);
+
+        assert_eq!(
+            encrypt_with("Ça va", secret, &alphabet, UnknownSymbol::Reject),
+            Err(CipherError::InvalidInputSymbol('Ç'))
+        );
+    }
+
+    #[test]
+    fn encrypt_decrypt_preserving_case() {
+        let message = "Attack at dawn!";
+        let result = encrypt_preserving_case(message, "LEMON").unwrap();
+        assert_eq!(result, "Lxfopv ef rnhr!");
+        assert_eq!(decrypt_preserving_case(&result, "lemon").unwrap(), message);
+    }
+
+    #[test]
+    fn encrypt_decrypt_through_traits() {
+        let cipher = VigenereCipher::default();
+        let secret = "secret".to_string();
+        let encrypted = Cipher::encrypt(&cipher, &secret, b"Hello World").unwrap();
+        assert_eq!(encrypted, b" JOCTTOTUCI");
+        assert_eq!(
+            Cipher::decrypt(&cipher, &secret, &encrypted).unwrap(),
+            b"HELLO WORLD"
+        );
+        assert_eq!(
+            cipher.key_stream(&"abc".to_string(), 5).unwrap(),
+            vec![1, 2, 3, 1, 2]
+        );
+    }
+
+    #[test]
+    fn crack_encrypted_message() {
+        let message = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG WHILE THE CAT WATCHES FROM THE WINDOW \
+            IT WAS THE BEST OF TIMES IT WAS THE WORST OF TIMES IT WAS THE AGE OF WISDOM IT WAS THE AGE OF \
+            FOOLISHNESS IT WAS THE EPOCH OF BELIEF IT WAS THE EPOCH OF INCREDULITY IT WAS THE SEASON OF LIGHT \
+            IT WAS THE SEASON OF DARKNESS IT WAS THE SPRING OF HOPE IT WAS THE WINTER OF DESPAIR";
+        let secret = "LEMON";
+        let cipher_text = encrypt(message, secret).unwrap();
+
+        let candidates = crack(&cipher_text);
+        assert!(!candidates.is_empty());
+        assert_eq!(candidates[0].0, secret);
+        assert_eq!(candidates[0].2, message);
+
+        assert_eq!(crack(""), vec![]);
+    }
+
+    fn encrypt_mode(plain_text: &str, secret: &str, mode: Mode) -> String {
+        encrypt_mode_with(
+            plain_text,
+            secret,
+            mode,
+            &Alphabet::letters(),
+            UnknownSymbol::PassThrough,
+        )
+        .unwrap()
+    }
+
+    fn decrypt_mode(cipher_text: &str, secret: &str, mode: Mode) -> String {
+        decrypt_mode_with(
+            cipher_text,
+            secret,
+            mode,
+            &Alphabet::letters(),
+            UnknownSymbol::PassThrough,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn encrypt_decrypt_modes() {
+        let message = "Attack at dawn!";
+        let cases = [
+            (Mode::Repeating, "LEMON", "Lxfopv ef rnhr!"),
+            (Mode::Autokey, "QUEENLY", "Qnxepv yt wtwp!"),
+            (Mode::Beaufort, "LEMON", "Lltolb et lnpr!"),
+            (Mode::VariantBeaufort, "LEMON", "Pphmpz wh pnlj!"),
+        ];
+        for (mode, secret, expected) in cases {
+            let result = encrypt_mode(message, secret, mode);
+            assert_eq!(result, expected, "{mode:?}");
+            assert_eq!(decrypt_mode(&result, secret, mode), message, "{mode:?}");
+        }
+
+        // beaufort is its own inverse
+        assert_eq!(
+            encrypt_mode("Lltolb et lnpr!", "LEMON", Mode::Beaufort),
+            message
+        );
+
+        let running_key = "It was the best of times, it was the worst of times";
+        let result = encrypt_mode(message, running_key, Mode::RunningKey);
+        assert_eq!(result, "Impaud hx eeog!");
+        assert_eq!(
+            decrypt_mode(&result, running_key, Mode::RunningKey),
+            message
+        );
+        assert_eq!(
+            encrypt_mode_with(
+                message,
+                "short",
+                Mode::RunningKey,
+                &Alphabet::letters(),
+                UnknownSymbol::PassThrough
+            ),
+            Err(CipherError::KeyTooShort {
+                required: 12,
+                available: 5
+            })
+        );
+
+        let cipher = VigenereCipher::default().with_mode("autokey".parse().unwrap());
+        let secret = "QUEENLY".to_string();
+        let encrypted = Cipher::encrypt(&cipher, &secret, b"HELLO WORLD").unwrap();
+        assert_eq!(
+            Cipher::decrypt(&cipher, &secret, &encrypted).unwrap(),
+            b"HELLO WORLD"
+        );
+        assert!(cipher.key_stream(&secret, 5).is_err());
+        assert_eq!(
+            "caesar".parse::<Mode>().unwrap_err().to_string(),
+            "invalid mode 'caesar', expected repeating, autokey, beaufort, variant-beaufort or running-key"
+        );
+    }
+
+    #[test]
+    fn crack_autokey_message() {
+        let message = "IT WAS THE BEST OF TIMES IT WAS THE WORST OF TIMES IT WAS THE AGE OF WISDOM \
+            IT WAS THE AGE OF FOOLISHNESS IT WAS THE EPOCH OF BELIEF IT WAS THE EPOCH OF INCREDULITY";
+        for secret in ["KEY", "QUEENLY"] {
+            let cipher_text = encrypt_mode_with(
+                message,
+                secret,
+                Mode::Autokey,
+                &Alphabet::default(),
+                UnknownSymbol::PassThrough,
+            )
+            .unwrap();
+
+            let candidates = crack_autokey(&cipher_text);
+            assert_eq!(candidates[0].0, secret);
+            assert_eq!(candidates[0].2, message);
+        }
+    }
 }